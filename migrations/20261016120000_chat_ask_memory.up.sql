@@ -0,0 +1,3 @@
+-- Per-chat switch for /ask conversation memory (history load and save).
+ALTER TABLE chats
+ADD COLUMN ask_memory BOOLEAN NOT NULL DEFAULT true;
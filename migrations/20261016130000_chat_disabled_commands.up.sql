@@ -0,0 +1,3 @@
+-- Commands turned off by chat admins with /disable (names without the slash).
+ALTER TABLE chats
+ADD COLUMN disabled_commands TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,57 @@
+-- Also return the age of each turn, for relative timestamps in the history.
+-- The result type changes, which CREATE OR REPLACE can't do.
+DROP FUNCTION IF EXISTS public.get_recent_messages(TEXT, BIGINT, BIGINT, INT);
+
+CREATE FUNCTION public.get_recent_messages(
+  p_lang TEXT,
+  p_user_telegram_id BIGINT,
+  p_chat_telegram_id BIGINT,
+  p_limit INT
+)
+RETURNS TABLE(content TEXT, ia_response TEXT, age_secs BIGINT)
+LANGUAGE sql
+AS $$
+WITH
+  ins_language AS (
+    INSERT INTO languages (name)
+    VALUES (p_lang)
+    ON CONFLICT DO NOTHING
+    RETURNING id
+  ),
+  language AS (
+    SELECT id FROM ins_language
+    UNION ALL
+    SELECT id
+    FROM languages
+    WHERE name = p_lang
+      AND deleted_at IS NULL
+    LIMIT 1
+  ),
+  ins_user AS (
+    INSERT INTO users (telegram_id, lang_id)
+    SELECT p_user_telegram_id, id FROM language
+    ON CONFLICT DO NOTHING
+  ),
+  ins_chat AS (
+    INSERT INTO chats (telegram_id)
+    VALUES (p_chat_telegram_id)
+    ON CONFLICT DO NOTHING
+  ),
+  msgs AS (
+    SELECT
+      m.content,
+      m.ia_response,
+      EXTRACT(EPOCH FROM now() - m.created_at)::BIGINT AS age_secs
+    FROM messages m
+    WHERE m.user_telegram_id = p_user_telegram_id
+      AND m.chat_telegram_id = p_chat_telegram_id
+      AND m.deleted_at IS NULL
+      AND m.is_cleared = FALSE
+    ORDER BY m.created_at DESC
+    LIMIT p_limit
+  )
+SELECT content, ia_response, age_secs FROM msgs
+UNION ALL
+SELECT NULL, NULL, NULL
+WHERE NOT EXISTS (SELECT 1 FROM msgs);
+$$;
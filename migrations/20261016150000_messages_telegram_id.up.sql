@@ -0,0 +1,4 @@
+-- Telegram id of the command message behind each history row, so an edited command's
+-- re-run can replace the turn it produced. Older rows stay NULL.
+ALTER TABLE messages
+ADD COLUMN message_telegram_id BIGINT;
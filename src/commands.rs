@@ -12,6 +12,18 @@ pub enum Command {
     #[command(description = "reset the chat history.")]
     Reset,
 
+    #[command(description = "forget the last N messages of the history (default 1).")]
+    Forget(String),
+
+    #[command(description = "turn /ask conversation memory on or off for this chat.")]
+    Memory(String),
+
+    #[command(description = "admin: enable a command in this chat.")]
+    Enable(String),
+
+    #[command(description = "admin: disable a command in this chat.")]
+    Disable(String),
+
     #[command(description = "the start command.")]
     Start,
 
@@ -21,6 +33,81 @@ pub enum Command {
     #[command(description = "respond using AI and your web resource")]
     Search(String),
 
+    #[command(description = "respond using AI, also as a voice message")]
+    Say(String),
+
+    #[command(description = "admin: test scraping a URL without the model.")]
+    ScrapeTest(String),
+
+    #[command(description = "check that the bot is up (details for admins).")]
+    Status,
+
     #[command(description = "display this text.")]
     Help,
 }
+
+impl Command {
+    // Lowercase name without the slash, as used in config lists.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Ask(_) => "ask",
+            Command::Repeat(_) => "repeat",
+            Command::Reset => "reset",
+            Command::Forget(_) => "forget",
+            Command::Memory(_) => "memory",
+            Command::Enable(_) => "enable",
+            Command::Disable(_) => "disable",
+            Command::Start => "start",
+            Command::Dollar(_) => "dollar",
+            Command::Search(_) => "search",
+            Command::Say(_) => "say",
+            Command::ScrapeTest(_) => "scrapetest",
+            Command::Status => "status",
+            Command::Help => "help",
+        }
+    }
+
+    // Free text that is sent to a model, for the commands that take one.
+    pub fn prompt(&self) -> Option<&str> {
+        match self {
+            Command::Ask(text) | Command::Search(text) | Command::Say(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+// True for a command explicitly addressed to another bot (`/ask@otherbot`), which groups
+// with several bots deliver to all of them.
+pub fn addressed_to_other_bot(text: &str, bot_name: &str) -> bool {
+    let Some(command) = text.strip_prefix('/') else {
+        return false;
+    };
+    let command = command.split_whitespace().next().unwrap_or_default();
+    command
+        .split_once('@')
+        .is_some_and(|(_, mention)| !mention.eq_ignore_ascii_case(bot_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Command, addressed_to_other_bot};
+    use teloxide::utils::command::BotCommands;
+
+    #[test]
+    fn commands_for_other_bots_are_ignored() {
+        assert!(addressed_to_other_bot("/ask@anotherbot hi", "scrapingbot"));
+        assert!(Command::parse("/ask@anotherbot hi", "scrapingbot").is_err());
+
+        // Ours, with or without the mention.
+        assert!(!addressed_to_other_bot(
+            "/ask@ScrapingBot hi",
+            "scrapingbot"
+        ));
+        assert!(matches!(
+            Command::parse("/ask@ScrapingBot hi", "scrapingbot"),
+            Ok(Command::Ask(text)) if text == "hi"
+        ));
+        assert!(!addressed_to_other_bot("/ask hi@anotherbot", "scrapingbot"));
+        assert!(!addressed_to_other_bot("hi @anotherbot", "scrapingbot"));
+    }
+}
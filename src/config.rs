@@ -1,6 +1,9 @@
 use dotenvy::dotenv;
-use std::env;
+use std::{
+    collections::HashMap, env, fmt::Display, ops::RangeInclusive, str::FromStr, time::Duration,
+};
 use thiserror::Error;
+use tracing::info;
 
 #[derive(Error, Debug)]
 pub enum ConfigError {
@@ -10,6 +13,254 @@ pub enum ConfigError {
     InvalidHosting(String),
     #[error("invalid WEBHOOK_URL: {0}")]
     InvalidWebhookUrl(String),
+    #[error("invalid {0} value (expected true|false): {1}")]
+    InvalidFlag(&'static str, String),
+    #[error("invalid {0} regex: {1}")]
+    InvalidRegex(&'static str, String),
+    #[error("invalid ASK_MODEL_CHAIN entry (expected provider:model): {0}")]
+    InvalidModelChain(String),
+    #[error("invalid KEEPALIVE_URL: {0}")]
+    InvalidKeepaliveUrl(String),
+    #[error("invalid ADMIN_USER_IDS entry (expected a Telegram user id): {0}")]
+    InvalidAdminId(String),
+    #[error("invalid {0} value: {1}")]
+    InvalidNumber(&'static str, String),
+    #[error("invalid system prompt override: {0}")]
+    InvalidSystemPrompt(String),
+    #[error("invalid MODEL_ALIASES entry (expected alias=provider:model): {0}")]
+    InvalidModelAlias(String),
+    #[error("invalid ANSWER_PROFILES entry (expected command:profile): {0}")]
+    InvalidAnswerProfile(String),
+}
+
+// Read an optional comma-separated list of command names, normalized to lowercase without '/'.
+fn env_command_list(name: &str) -> Vec<String> {
+    env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(|c| c.trim().trim_start_matches('/').to_lowercase())
+        .filter(|c| !c.is_empty())
+        .collect()
+}
+
+// Read a comma-separated list of host names, lowercased and without a leading "*." or ".".
+fn env_domain_list(name: &str) -> Vec<String> {
+    env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(|d| {
+            d.trim()
+                .trim_start_matches("*.")
+                .trim_start_matches('.')
+                .to_lowercase()
+        })
+        .filter(|d| !d.is_empty())
+        .collect()
+}
+
+// Read an optional boolean env var, accepting the same spellings as HOSTING.
+fn env_flag(name: &'static str, default: bool) -> Result<bool, ConfigError> {
+    match env::var(name) {
+        Ok(raw) if !raw.trim().is_empty() => match raw.trim().to_lowercase().as_str() {
+            "true" | "1" | "yes" => Ok(true),
+            "false" | "0" | "no" => Ok(false),
+            other => Err(ConfigError::InvalidFlag(name, other.to_string())),
+        },
+        _ => Ok(default),
+    }
+}
+
+// Read an optional number that must lie within `range`; unset or empty means `default`.
+fn env_number<T>(name: &'static str, default: T, range: RangeInclusive<T>) -> Result<T, ConfigError>
+where
+    T: FromStr + PartialOrd + Display,
+{
+    Ok(env_optional_number(name, range)?.unwrap_or(default))
+}
+
+// Like `env_number`, for settings whose default isn't a constant: unset or empty is None.
+fn env_optional_number<T>(
+    name: &'static str,
+    range: RangeInclusive<T>,
+) -> Result<Option<T>, ConfigError>
+where
+    T: FromStr + PartialOrd + Display,
+{
+    let raw = match env::var(name) {
+        Ok(raw) if !raw.trim().is_empty() => raw,
+        _ => return Ok(None),
+    };
+    match raw.trim().parse::<T>() {
+        Ok(value) if range.contains(&value) => Ok(Some(value)),
+        _ => Err(ConfigError::InvalidNumber(
+            name,
+            format!("{raw} (expected {}..={})", range.start(), range.end()),
+        )),
+    }
+}
+
+// Public URL advertised by a known PaaS host, with the webhook path appended.
+// Render gives a full URL, Railway a bare domain and Fly only the app name.
+fn platform_webhook_url() -> Result<Option<(&'static str, url::Url)>, ConfigError> {
+    let non_empty = |name: &str| env::var(name).ok().filter(|v| !v.trim().is_empty());
+
+    let (platform, base) = if let Some(url) = non_empty("RENDER_EXTERNAL_URL") {
+        ("Render", url)
+    } else if let Some(domain) = non_empty("RAILWAY_PUBLIC_DOMAIN") {
+        ("Railway", format!("https://{domain}"))
+    } else if let Some(app) = non_empty("FLY_APP_NAME") {
+        ("Fly", format!("https://{app}.fly.dev"))
+    } else {
+        return Ok(None);
+    };
+
+    let raw = format!("{}/webhook", base.trim().trim_end_matches('/'));
+    match url::Url::parse(&raw) {
+        // Telegram only delivers webhooks over HTTPS.
+        Ok(url) if url.scheme() == "https" && url.host_str().is_some() => Ok(Some((platform, url))),
+        _ => Err(ConfigError::InvalidWebhookUrl(raw)),
+    }
+}
+
+// Default command deadline: the slowest model path allowed by the model timeouts, plus a
+// margin for scraping, history and sending. That is a photo /ask (vision call, then every
+// chain model) or a large-page /search (every chunk summary, then the answer; pass 0
+// chunks when map-reduce is off).
+fn default_command_timeout_secs(
+    text_timeout_secs: u64,
+    vision_timeout_secs: u64,
+    chain_len: usize,
+    max_chunks: usize,
+) -> u64 {
+    const MARGIN_SECS: u64 = 60;
+    let ask = vision_timeout_secs + text_timeout_secs * chain_len as u64;
+    let search = text_timeout_secs * (max_chunks as u64 + 1);
+    ask.max(search) + MARGIN_SECS
+}
+
+// Model providers the bot can call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Provider {
+    Groq,
+}
+
+impl Provider {
+    // Temperatures the provider accepts.
+    pub fn temperature_range(self) -> (f32, f32) {
+        match self {
+            Provider::Groq => (0.0, 2.0),
+        }
+    }
+}
+
+// One entry of a model fallback chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainModel {
+    pub provider: Provider,
+    pub model: String,
+}
+
+// Parse a comma-separated `provider:model` list, e.g. "groq:openai/gpt-oss-120b".
+pub fn parse_model_chain(raw: &str) -> Result<Vec<ChainModel>, ConfigError> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let invalid = || ConfigError::InvalidModelChain(entry.to_string());
+            let (provider, model) = entry.split_once(':').ok_or_else(invalid)?;
+            let provider = match provider.trim().to_lowercase().as_str() {
+                "groq" => Provider::Groq,
+                _ => return Err(invalid()),
+            };
+            let model = model.trim();
+            if model.is_empty() {
+                return Err(invalid());
+            }
+            Ok(ChainModel {
+                provider,
+                model: model.to_string(),
+            })
+        })
+        .collect()
+}
+
+// Parse comma-separated `alias=provider:model` pairs, e.g. "pro=groq:openai/gpt-oss-120b".
+pub fn parse_model_aliases(raw: &str) -> Result<HashMap<String, ChainModel>, ConfigError> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let invalid = || ConfigError::InvalidModelAlias(entry.to_string());
+            let (alias, model) = entry.split_once('=').ok_or_else(invalid)?;
+            let alias = alias.trim().to_lowercase();
+            let valid_alias = !alias.is_empty()
+                && alias
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            let mut chain = parse_model_chain(model).map_err(|_| invalid())?;
+            if !valid_alias || chain.len() != 1 {
+                return Err(invalid());
+            }
+            Ok((alias, chain.remove(0)))
+        })
+        .collect()
+}
+
+// Parse a ';'-separated list of case-insensitive regexes (patterns may contain commas and '|').
+pub fn parse_denylist(raw: &str) -> Result<Vec<regex::Regex>, ConfigError> {
+    raw.split(';')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| {
+            regex::RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| ConfigError::InvalidRegex("PROMPT_DENYLIST", e.to_string()))
+        })
+        .collect()
+}
+
+// Read the answer system prompt override: inline (SYSTEM_PROMPT_OVERRIDE) or from a file
+// (SYSTEM_PROMPT_FILE), not both. None keeps the embedded prompt.
+fn system_prompt_override() -> Result<Option<String>, ConfigError> {
+    let non_empty = |name: &str| env::var(name).ok().filter(|v| !v.trim().is_empty());
+
+    match (
+        non_empty("SYSTEM_PROMPT_OVERRIDE"),
+        non_empty("SYSTEM_PROMPT_FILE"),
+    ) {
+        (Some(_), Some(_)) => Err(ConfigError::InvalidSystemPrompt(
+            "set either SYSTEM_PROMPT_OVERRIDE or SYSTEM_PROMPT_FILE, not both".to_string(),
+        )),
+        (Some(inline), None) => Ok(Some(inline)),
+        (None, Some(path)) => {
+            let content = std::fs::read_to_string(path.trim())
+                .map_err(|e| ConfigError::InvalidSystemPrompt(format!("{path}: {e}")))?;
+            if content.trim().is_empty() {
+                return Err(ConfigError::InvalidSystemPrompt(format!("{path} is empty")));
+            }
+            Ok(Some(content))
+        }
+        (None, None) => Ok(None),
+    }
+}
+
+// Runtime replacement for the embedded answer system prompt (think_and_format.md).
+#[derive(Clone, Debug, Default)]
+pub struct PromptConfig {
+    pub system_override: Option<String>,
+    // /search keeps the embedded prompt unless this is set.
+    pub override_search: bool,
+}
+
+impl PromptConfig {
+    // Base system prompt for an answer: the override when it applies, else `embedded`.
+    pub fn answer_base(&self, embedded: String, search: bool) -> String {
+        match &self.system_override {
+            Some(custom) if !search || self.override_search => custom.clone(),
+            _ => embedded,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -17,18 +268,342 @@ pub struct Models {
     pub vision: String,
     pub preprocessing: String,
     pub thinking: String,
+    // Explicit reasoning budget for /ask; None lets the prompt decide.
+    pub thinking_budget: Option<i32>,
+    // Per-call deadlines; image analysis is much slower than text.
+    pub text_timeout_secs: u64,
+    pub vision_timeout_secs: u64,
+    // Models tried in order by /ask until one answers; defaults to the thinking model.
+    pub ask_chain: Vec<ChainModel>,
+    // Start /ask answers with the model that produced them.
+    pub show_ask_model: bool,
+    // Models /ask users can pick with a leading "[alias]", e.g. "[pro] question".
+    pub aliases: HashMap<String, ChainModel>,
+    // Sampling temperature for answers; clamped per provider when sent.
+    pub temperature: f32,
+}
+
+// Limits for the /search map-reduce path used on pages larger than one request.
+#[derive(Clone, Debug)]
+pub struct SearchConfig {
+    pub chunk_chars: usize,
+    pub max_chunks: usize,
+    // Send the page's og:image after the answer.
+    pub og_preview: bool,
+    // Start the answer with the language it was requested in.
+    pub show_lang: bool,
+}
+
+// Limits applied to the saved conversation history.
+#[derive(Clone, Debug)]
+pub struct HistoryConfig {
+    pub max_chars: usize,
+    // Active rows kept per user/chat after each insert; 0 disables pruning.
+    pub retention: i64,
+    // Turns sent to the model carry how long ago they were said ("2 hours ago").
+    pub timestamps: bool,
+}
+
+// Greeting sent when the bot is added to a group or a member joins.
+// The message supports {name}, {chat} and {commands} placeholders.
+#[derive(Clone, Debug)]
+pub struct WelcomeConfig {
+    pub enabled: bool,
+    pub message: String,
+}
+
+// OpenAI-compatible text-to-speech endpoint used by /say.
+#[derive(Clone)]
+pub struct TtsConfig {
+    pub enabled: bool,
+    pub url: String,
+    pub api_key: String,
+    pub model: String,
+    pub voice: String,
+    pub format: String,
+    // Attach the (redacted) request to errors, to debug malformed requests.
+    pub debug_requests: bool,
+}
+
+impl std::fmt::Debug for TtsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TtsConfig")
+            .field("enabled", &self.enabled)
+            .field("url", &self.url)
+            .field("api_key", &"<redacted>")
+            .field("model", &self.model)
+            .field("voice", &self.voice)
+            .field("format", &self.format)
+            .field("debug_requests", &self.debug_requests)
+            .finish()
+    }
+}
+
+// Commands hidden from /help: in groups for non-admins, or for everyone but group admins
+// and ADMIN_USER_IDS.
+#[derive(Clone, Debug, Default)]
+pub struct HelpConfig {
+    pub group_hidden: Vec<String>,
+    pub admin_only: Vec<String>,
+}
+
+// Output style of a command's answer, chosen per command with ANSWER_PROFILES.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnswerProfile {
+    // Full answer with Telegram formatting.
+    Conversational,
+    // Like conversational, with the pages /search read listed as sources.
+    Structured,
+    // Short answers.
+    Terse,
+    // Text only: markup is stripped.
+    Plain,
+}
+
+impl AnswerProfile {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "conversational" => Some(Self::Conversational),
+            "structured" => Some(Self::Structured),
+            "terse" => Some(Self::Terse),
+            "plain" => Some(Self::Plain),
+            _ => None,
+        }
+    }
+
+    // Style of a command nobody configured.
+    fn default_for(command: &str) -> Self {
+        match command {
+            "search" => Self::Structured,
+            "dollar" => Self::Terse,
+            _ => Self::Conversational,
+        }
+    }
+
+    // Longest answer in chars; 0 leaves it to MAX_ANSWER_CHARS.
+    pub fn max_chars(self) -> usize {
+        match self {
+            Self::Terse => 800,
+            _ => 0,
+        }
+    }
+
+    pub fn show_sources(self) -> bool {
+        self == Self::Structured
+    }
+
+    // Sent as Telegram HTML; plain answers have their tags removed.
+    pub fn html(self) -> bool {
+        self != Self::Plain
+    }
+}
+
+// Read ANSWER_PROFILES, e.g. "ask:terse,search:plain".
+fn env_answer_profiles() -> Result<HashMap<String, AnswerProfile>, ConfigError> {
+    env::var("ANSWER_PROFILES")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .split_once(':')
+                .and_then(|(command, profile)| {
+                    let command = command.trim().trim_start_matches('/').to_lowercase();
+                    Some((command, AnswerProfile::parse(profile)?))
+                })
+                .filter(|(command, _)| !command.is_empty())
+                .ok_or_else(|| ConfigError::InvalidAnswerProfile(entry.to_string()))
+        })
+        .collect()
+}
+
+// Post-processing applied to every answer before it is sent.
+#[derive(Clone, Debug)]
+pub struct AnswerConfig {
+    // Matches are replaced by `redact_replacement` (e.g. phone numbers).
+    pub redact: Option<regex::Regex>,
+    pub redact_replacement: String,
+    // Appended after the answer; may contain Telegram HTML.
+    pub footer: Option<String>,
+    // Extra instructions added to the system prompt of every answer.
+    pub system_prompt: Option<String>,
+    // Answers made of one fenced code block are sent as a <pre> block with its language.
+    pub code_blocks: bool,
+    // Answers longer than this (UTF-16 units) are sent as a .txt file; 0 disables it.
+    pub file_threshold: usize,
+    // Answers are trimmed to this many chars instead of being split; 0 disables it.
+    pub max_chars: usize,
+    // Shown where text was cut short; escaped for the message's parse mode.
+    pub truncation_label: String,
+    // Edits of a command within this many seconds re-run it and update the reply; 0 disables it.
+    pub edit_window_secs: u64,
+    // Error replies (database, model, fetch failures) are deleted after this many seconds;
+    // 0 keeps them.
+    pub error_delete_secs: u64,
+    // /ask and /search reply with "…" right away and edit it into the answer.
+    pub placeholder: bool,
+    // Commands whose style differs from their default profile.
+    pub profiles: HashMap<String, AnswerProfile>,
+}
+
+impl AnswerConfig {
+    // Output style of `command` (lowercase name, as in Command::name).
+    pub fn profile(&self, command: &str) -> AnswerProfile {
+        self.profiles
+            .get(command)
+            .copied()
+            .unwrap_or_else(|| AnswerProfile::default_for(command))
+    }
+
+    pub fn error_delete_after(&self) -> Option<Duration> {
+        (self.error_delete_secs > 0).then(|| Duration::from_secs(self.error_delete_secs))
+    }
+}
+
+impl Default for AnswerConfig {
+    fn default() -> Self {
+        Self {
+            redact: None,
+            redact_replacement: "[redacted]".to_string(),
+            footer: None,
+            system_prompt: None,
+            code_blocks: true,
+            file_threshold: 0,
+            max_chars: 0,
+            truncation_label: "…(truncated)".to_string(),
+            edit_window_secs: 600,
+            error_delete_secs: 0,
+            placeholder: false,
+            profiles: HashMap::new(),
+        }
+    }
+}
+
+// Connection pool of the shared HTTP client (see http.rs).
+#[derive(Clone, Debug)]
+pub struct HttpConfig {
+    // Idle connections are closed after this many seconds (1..=3600).
+    pub pool_idle_timeout_secs: u64,
+    // Idle connections kept per host (0..=1024); 0 disables connection reuse.
+    pub pool_max_idle_per_host: usize,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            pool_idle_timeout_secs: 90,
+            pool_max_idle_per_host: 16,
+        }
+    }
+}
+
+// Prompts matching any denylist pattern are refused before reaching a model.
+#[derive(Clone, Debug)]
+pub struct ModerationConfig {
+    pub denylist: Vec<regex::Regex>,
+    pub refusal: String,
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            denylist: Vec::new(),
+            refusal: "I can't help with that request.".to_string(),
+        }
+    }
+}
+
+impl ModerationConfig {
+    pub fn is_denied(&self, prompt: &str) -> bool {
+        self.denylist.iter().any(|re| re.is_match(prompt))
+    }
+}
+
+// Commands disabled per chat type (comma-separated names, like the HELP_* lists).
+#[derive(Clone, Debug, Default)]
+pub struct ChatPolicyConfig {
+    pub private_disabled: Vec<String>,
+    // Groups and supergroups.
+    pub group_disabled: Vec<String>,
+    pub channel_disabled: Vec<String>,
+}
+
+// Who a message is attributed to when no real user is behind it.
+#[derive(Clone, Debug)]
+pub struct IdentityConfig {
+    // Messages sent on behalf of a chat (anonymous admins, channels) use that chat as sender.
+    pub sender_chat: bool,
+    // Answer language when the sender has none, which is always the case for chats.
+    pub default_lang: String,
+}
+
+impl Default for IdentityConfig {
+    fn default() -> Self {
+        Self {
+            sender_chat: true,
+            default_lang: "en".to_string(),
+        }
+    }
+}
+
+// CSS selectors tried in order to find the price on the BCV homepage.
+#[derive(Clone, Debug)]
+pub struct DollarConfig {
+    pub selectors: Vec<String>,
+    // BCV is fetched at most once per this many seconds, whatever the outcome.
+    pub min_interval_secs: u64,
+    // A fetched price is answered from memory for this many seconds; 0 disables it.
+    pub cache_ttl_secs: u64,
 }
 
 #[derive(Clone)]
 pub struct AppConfig {
     pub database_url: String,
     pub scrapedo_token: String,
+    // scrape.do requests in flight at once, across all chats; 0 disables the limit.
+    pub scrapedo_max_concurrency: usize,
+    // Hosts (and their subdomains) fetched directly, skipping scrape.do and its credits.
+    pub scrape_direct_domains: Vec<String>,
     pub token: String,
     pub groq_api_key: String,
     pub hosting: bool,
     pub webhook_url: Option<url::Url>,
     pub port: u16,
+    // Pinged periodically in webhook mode so free-tier hosts don't sleep.
+    pub keepalive_url: Option<url::Url>,
+    pub keepalive_interval_secs: u64,
+    // Updates whose id was already handled within this many seconds are skipped (webhook
+    // retries); 0 disables it.
+    pub update_dedup_secs: u64,
+    // On SIGTERM/SIGINT, commands still running are cancelled and their users told to
+    // resend them.
+    pub shutdown_notice: bool,
+    // Telegram user ids allowed to run operator commands such as /scrapetest.
+    pub admin_ids: Vec<u64>,
+    // Deadline for a whole command run, in seconds; 0 disables it.
+    pub command_timeout_secs: u64,
+    // Commands processed at once per chat; extra ones are rejected. 0 disables it.
+    pub chat_max_concurrent: usize,
     pub models: Models,
+    pub prompt: PromptConfig,
+    pub search: SearchConfig,
+    pub history: HistoryConfig,
+    pub welcome: WelcomeConfig,
+    pub tts: TtsConfig,
+    pub help: HelpConfig,
+    pub chat_policy: ChatPolicyConfig,
+    pub identity: IdentityConfig,
+    pub dollar: DollarConfig,
+    pub answer: AnswerConfig,
+    pub moderation: ModerationConfig,
+    pub http: HttpConfig,
+}
+
+impl AppConfig {
+    pub fn is_admin(&self, user_id: u64) -> bool {
+        self.admin_ids.contains(&user_id)
+    }
 }
 
 impl std::fmt::Debug for AppConfig {
@@ -37,10 +612,31 @@ impl std::fmt::Debug for AppConfig {
             .field("database_url", &"<redacted>")
             .field("token", &"<redacted>")
             .field("scrapedo_token", &"<redacted>")
+            .field("scrapedo_max_concurrency", &self.scrapedo_max_concurrency)
+            .field("scrape_direct_domains", &self.scrape_direct_domains)
             .field("groq_api_key", &"<redacted>")
             .field("hosting", &self.hosting)
             .field("webhook_url", &self.webhook_url)
             .field("port", &self.port)
+            .field("keepalive_url", &self.keepalive_url)
+            .field("keepalive_interval_secs", &self.keepalive_interval_secs)
+            .field("update_dedup_secs", &self.update_dedup_secs)
+            .field("shutdown_notice", &self.shutdown_notice)
+            .field("admin_ids", &self.admin_ids)
+            .field("command_timeout_secs", &self.command_timeout_secs)
+            .field("chat_max_concurrent", &self.chat_max_concurrent)
+            .field("prompt", &self.prompt)
+            .field("search", &self.search)
+            .field("history", &self.history)
+            .field("welcome", &self.welcome)
+            .field("tts", &self.tts)
+            .field("help", &self.help)
+            .field("chat_policy", &self.chat_policy)
+            .field("identity", &self.identity)
+            .field("dollar", &self.dollar)
+            .field("answer", &self.answer)
+            .field("moderation", &self.moderation)
+            .field("http", &self.http)
             .finish()
     }
 }
@@ -67,16 +663,23 @@ impl AppConfig {
 
         let scrapedo_token =
             env::var("SCRAPEDO_TOKEN").map_err(|_| ConfigError::MissingEnv("SCRAPEDO_TOKEN"))?;
+        let scrapedo_max_concurrency = env_number("SCRAPEDO_MAX_CONCURRENCY", 0, 0..=1000)?;
+        let scrape_direct_domains = env_domain_list("SCRAPE_DIRECT_DOMAINS");
 
         let groq_api_key =
             env::var("GROQ_API_KEY").map_err(|_| ConfigError::MissingEnv("GROQ_API_KEY"))?;
 
-        let hosting_raw = env::var("HOSTING").map_err(|_| ConfigError::MissingEnv("HOSTING"))?;
+        // Explicit HOSTING/WEBHOOK_URL always win over the platform's values.
+        let platform = platform_webhook_url()?;
 
-        let hosting = match hosting_raw.to_lowercase().as_str() {
-            "true" | "1" | "yes" => true,
-            "false" | "0" | "no" => false,
-            other => return Err(ConfigError::InvalidHosting(other.to_string())),
+        let hosting = match env::var("HOSTING") {
+            Ok(hosting_raw) => match hosting_raw.to_lowercase().as_str() {
+                "true" | "1" | "yes" => true,
+                "false" | "0" | "no" => false,
+                other => return Err(ConfigError::InvalidHosting(other.to_string())),
+            },
+            Err(_) if platform.is_some() => true,
+            Err(_) => return Err(ConfigError::MissingEnv("HOSTING")),
         };
 
         let webhook_url = match env::var("WEBHOOK_URL") {
@@ -85,7 +688,10 @@ impl AppConfig {
                     url::Url::parse(&s).map_err(|_| ConfigError::InvalidWebhookUrl(s.clone()))?;
                 Some(parsed)
             }
-            _ => None,
+            _ => platform.map(|(name, url)| {
+                info!("Using webhook URL detected from {name}: {url}");
+                url
+            }),
         };
 
         let port = env::var("PORT")
@@ -93,6 +699,30 @@ impl AppConfig {
             .and_then(|s| s.parse::<u16>().ok())
             .unwrap_or(8080);
 
+        let keepalive_url = match env::var("KEEPALIVE_URL") {
+            Ok(s) if !s.trim().is_empty() => Some(
+                url::Url::parse(s.trim())
+                    .map_err(|_| ConfigError::InvalidKeepaliveUrl(s.clone()))?,
+            ),
+            _ => None,
+        };
+        let keepalive_interval_secs = env_number("KEEPALIVE_INTERVAL_SECS", 600, 1..=86_400)?;
+        let update_dedup_secs = env_number("UPDATE_DEDUP_WINDOW_SECS", 60, 0..=3600)?;
+        let shutdown_notice = env_flag("SHUTDOWN_NOTICE", false)?;
+
+        let admin_ids = env::var("ADMIN_USER_IDS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(|id| {
+                id.parse::<u64>()
+                    .map_err(|_| ConfigError::InvalidAdminId(id.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let chat_max_concurrent = env_number("CHAT_MAX_CONCURRENT", 1, 0..=1000)?;
+
         // Fix: read model env vars with defaults
         let vision = env::var("VISION_MODEL")
             .unwrap_or_else(|_| "meta-llama/llama-4-scout-17b-16e-instruct".to_string());
@@ -100,20 +730,187 @@ impl AppConfig {
             env::var("PREPROCESSING_MODEL").unwrap_or_else(|_| "openai/gpt-oss-20b".to_string());
         let thinking =
             env::var("THINKING_MODEL").unwrap_or_else(|_| "openai/gpt-oss-120b".to_string());
+        // Unset lets the heuristic pick a budget per prompt.
+        let thinking_budget = env_optional_number("THINKING_BUDGET", 0..=65_536)?;
+        let text_timeout_secs = env_number("TEXT_MODEL_TIMEOUT_SECS", 60, 1..=3600)?;
+        let vision_timeout_secs = env_number("VISION_MODEL_TIMEOUT_SECS", 120, 1..=3600)?;
+        let temperature = env_number("MODEL_TEMPERATURE", 0.0, 0.0..=2.0)?;
+        let mut ask_chain = parse_model_chain(&env::var("ASK_MODEL_CHAIN").unwrap_or_default())?;
+        let model_aliases = parse_model_aliases(&env::var("MODEL_ALIASES").unwrap_or_default())?;
+        if ask_chain.is_empty() {
+            ask_chain.push(ChainModel {
+                provider: Provider::Groq,
+                model: thinking.clone(),
+            });
+        }
+
+        // Pages above chunk_chars are summarized per chunk before answering; 0 (the
+        // default) sends the whole page to the main model instead.
+        let chunk_chars = env_number("SEARCH_CHUNK_CHARS", 0, 0..=1_000_000)?;
+        let max_chunks = env_number("SEARCH_MAX_CHUNKS", 6, 1..=100)?;
+
+        let command_timeout_secs = env_optional_number("COMMAND_TIMEOUT_SECS", 0..=86_400)?
+            .unwrap_or_else(|| {
+                default_command_timeout_secs(
+                    text_timeout_secs,
+                    vision_timeout_secs,
+                    ask_chain.len(),
+                    if chunk_chars > 0 { max_chunks } else { 0 },
+                )
+            });
+
+        // Longest content/answer saved per history row, in chars.
+        let history_max_chars = env_number("HISTORY_MAX_CHARS", 50_000, 1..=10_000_000)?;
+        let history_retention = env_number("HISTORY_RETENTION", 100, 0..=1_000_000)?;
+
+        let welcome_enabled = env_flag("WELCOME_ENABLED", false)?;
+        let welcome_message = env::var("WELCOME_MESSAGE")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| {
+                "Welcome, {name}! I'm TScrapingBot. This is what I can do in {chat}:\n\n{commands}"
+                    .to_string()
+            });
+
+        // TTS defaults to Groq's OpenAI-compatible speech endpoint and key.
+        let tts = TtsConfig {
+            enabled: env_flag("TTS_ENABLED", false)?,
+            url: env::var("TTS_URL")
+                .unwrap_or_else(|_| "https://api.groq.com/openai/v1/audio/speech".to_string()),
+            api_key: env::var("TTS_API_KEY").unwrap_or_else(|_| groq_api_key.clone()),
+            model: env::var("TTS_MODEL").unwrap_or_else(|_| "playai-tts".to_string()),
+            voice: env::var("TTS_VOICE").unwrap_or_else(|_| "Fritz-PlayAI".to_string()),
+            format: env::var("TTS_FORMAT").unwrap_or_else(|_| "mp3".to_string()),
+            debug_requests: env_flag("TTS_DEBUG_REQUESTS", false)?,
+        };
+
+        // Selectors are separated by '|' since CSS selectors may contain commas.
+        let mut bcv_selectors: Vec<String> = env::var("BCV_SELECTORS")
+            .unwrap_or_default()
+            .split('|')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if bcv_selectors.is_empty() {
+            bcv_selectors.push("#dolar strong".to_string());
+        }
+
+        let http = HttpConfig {
+            pool_idle_timeout_secs: env_number("HTTP_POOL_IDLE_TIMEOUT_SECS", 90, 1..=3600)?,
+            pool_max_idle_per_host: env_number("HTTP_POOL_MAX_IDLE_PER_HOST", 16, 0..=1024)?,
+        };
+
+        let moderation = ModerationConfig {
+            denylist: parse_denylist(&env::var("PROMPT_DENYLIST").unwrap_or_default())?,
+            refusal: env::var("PROMPT_DENYLIST_MESSAGE")
+                .ok()
+                .filter(|m| !m.trim().is_empty())
+                .unwrap_or_else(|| ModerationConfig::default().refusal),
+        };
+
+        let answer_redact = match env::var("ANSWER_REDACT_REGEX") {
+            Ok(raw) if !raw.trim().is_empty() => Some(
+                regex::Regex::new(&raw)
+                    .map_err(|e| ConfigError::InvalidRegex("ANSWER_REDACT_REGEX", e.to_string()))?,
+            ),
+            _ => None,
+        };
 
         Ok(Self {
             database_url,
             token,
             scrapedo_token,
+            scrapedo_max_concurrency,
+            scrape_direct_domains,
             groq_api_key,
             hosting,
             webhook_url,
             port,
+            keepalive_url,
+            keepalive_interval_secs,
+            update_dedup_secs,
+            shutdown_notice,
+            admin_ids,
+            command_timeout_secs,
+            chat_max_concurrent,
+            prompt: PromptConfig {
+                system_override: system_prompt_override()?,
+                override_search: env_flag("SYSTEM_PROMPT_OVERRIDE_SEARCH", false)?,
+            },
             models: Models {
                 vision,
                 preprocessing,
                 thinking,
+                thinking_budget,
+                text_timeout_secs,
+                vision_timeout_secs,
+                ask_chain,
+                show_ask_model: env_flag("ASK_SHOW_MODEL", false)?,
+                aliases: model_aliases,
+                temperature,
             },
+            search: SearchConfig {
+                chunk_chars,
+                max_chunks,
+                og_preview: env_flag("SEARCH_OG_PREVIEW", false)?,
+                show_lang: env_flag("SEARCH_SHOW_LANG", false)?,
+            },
+            history: HistoryConfig {
+                max_chars: history_max_chars,
+                retention: history_retention,
+                timestamps: env_flag("HISTORY_TIMESTAMPS", false)?,
+            },
+            welcome: WelcomeConfig {
+                enabled: welcome_enabled,
+                message: welcome_message,
+            },
+            tts,
+            help: HelpConfig {
+                group_hidden: env_command_list("HELP_GROUP_HIDDEN"),
+                admin_only: env_command_list("HELP_ADMIN_ONLY"),
+            },
+            chat_policy: ChatPolicyConfig {
+                private_disabled: env_command_list("COMMANDS_DISABLED_PRIVATE"),
+                group_disabled: env_command_list("COMMANDS_DISABLED_GROUP"),
+                channel_disabled: env_command_list("COMMANDS_DISABLED_CHANNEL"),
+            },
+            identity: IdentityConfig {
+                sender_chat: env_flag("SENDER_CHAT_IDENTITY", true)?,
+                default_lang: env::var("DEFAULT_LANG")
+                    .ok()
+                    .map(|l| l.trim().to_string())
+                    .filter(|l| !l.is_empty())
+                    .unwrap_or_else(|| "en".to_string()),
+            },
+            dollar: DollarConfig {
+                selectors: bcv_selectors,
+                min_interval_secs: env_number("BCV_MIN_INTERVAL_SECS", 60, 0..=86_400)?,
+                cache_ttl_secs: env_number("BCV_CACHE_TTL_SECS", 0, 0..=86_400)?,
+            },
+            answer: AnswerConfig {
+                redact: answer_redact,
+                redact_replacement: env::var("ANSWER_REDACT_REPLACEMENT")
+                    .unwrap_or_else(|_| "[redacted]".to_string()),
+                footer: env::var("ANSWER_FOOTER")
+                    .ok()
+                    .filter(|f| !f.trim().is_empty()),
+                system_prompt: env::var("ANSWER_SYSTEM_PROMPT")
+                    .ok()
+                    .filter(|p| !p.trim().is_empty()),
+                code_blocks: env_flag("ANSWER_CODE_BLOCKS", true)?,
+                file_threshold: env_number("ANSWER_FILE_THRESHOLD", 0, 0..=1_000_000)?,
+                max_chars: env_number("MAX_ANSWER_CHARS", 0, 0..=1_000_000)?,
+                truncation_label: env::var("TRUNCATION_INDICATOR")
+                    .ok()
+                    .filter(|l| !l.trim().is_empty())
+                    .unwrap_or_else(|| "…(truncated)".to_string()),
+                edit_window_secs: env_number("EDIT_RERUN_WINDOW_SECS", 600, 0..=86_400)?,
+                error_delete_secs: env_number("ERROR_AUTO_DELETE_SECS", 0, 0..=86_400)?,
+                placeholder: env_flag("ANSWER_PLACEHOLDER", false)?,
+                profiles: env_answer_profiles()?,
+            },
+            moderation,
+            http,
         })
     }
 }
@@ -149,6 +946,16 @@ mod tests {
         assert_eq!(cfg.groq_api_key, "asdfg");
         assert!(cfg.hosting);
         assert_eq!(cfg.port, 1234);
+        assert!(cfg.admin_ids.is_empty());
+        assert!(cfg.keepalive_url.is_none());
+        assert_eq!(cfg.keepalive_interval_secs, 600);
+        assert_eq!(cfg.update_dedup_secs, 60);
+        assert!(!cfg.shutdown_notice);
+        // Map-reduce is off: the photo /ask path (120s vision, one 60s chain model) plus the margin.
+        assert_eq!(cfg.command_timeout_secs, 240);
+        assert_eq!(cfg.chat_max_concurrent, 1);
+        assert_eq!(cfg.scrapedo_max_concurrency, 0);
+        assert!(cfg.scrape_direct_domains.is_empty());
         assert_eq!(
             cfg.webhook_url.unwrap().as_str(),
             "https://example.com/hook"
@@ -160,6 +967,57 @@ mod tests {
         );
         assert_eq!(cfg.models.preprocessing, "openai/gpt-oss-20b");
         assert_eq!(cfg.models.thinking, "openai/gpt-oss-120b");
+        assert_eq!(cfg.models.thinking_budget, None);
+        assert_eq!(cfg.models.text_timeout_secs, 60);
+        assert_eq!(cfg.models.vision_timeout_secs, 120);
+        assert_eq!(cfg.models.temperature, 0.0);
+        assert_eq!(
+            cfg.models.ask_chain,
+            vec![ChainModel {
+                provider: Provider::Groq,
+                model: "openai/gpt-oss-120b".into(),
+            }]
+        );
+        assert!(!cfg.models.show_ask_model);
+        assert!(cfg.models.aliases.is_empty());
+        assert!(cfg.prompt.system_override.is_none());
+        assert!(!cfg.prompt.override_search);
+        assert_eq!(cfg.search.chunk_chars, 0);
+        assert_eq!(cfg.search.max_chunks, 6);
+        assert!(!cfg.search.og_preview);
+        assert!(!cfg.search.show_lang);
+        assert_eq!(cfg.history.max_chars, 50_000);
+        assert_eq!(cfg.history.retention, 100);
+        assert!(!cfg.history.timestamps);
+        assert!(!cfg.welcome.enabled);
+        assert!(cfg.welcome.message.contains("{commands}"));
+        assert!(!cfg.tts.enabled);
+        assert_eq!(cfg.tts.api_key, "asdfg");
+        assert!(!cfg.tts.debug_requests);
+        assert!(cfg.help.group_hidden.is_empty());
+        assert!(cfg.chat_policy.group_disabled.is_empty());
+        assert!(cfg.identity.sender_chat);
+        assert_eq!(cfg.identity.default_lang, "en");
+        assert_eq!(cfg.dollar.selectors, vec!["#dolar strong"]);
+        assert_eq!(cfg.dollar.min_interval_secs, 60);
+        assert_eq!(cfg.dollar.cache_ttl_secs, 0);
+        assert!(cfg.answer.redact.is_none());
+        assert!(cfg.answer.footer.is_none());
+        assert!(cfg.answer.system_prompt.is_none());
+        assert!(cfg.answer.code_blocks);
+        assert_eq!(cfg.answer.file_threshold, 0);
+        assert_eq!(cfg.answer.max_chars, 0);
+        assert_eq!(cfg.answer.truncation_label, "…(truncated)");
+        assert_eq!(cfg.answer.edit_window_secs, 600);
+        assert_eq!(cfg.answer.error_delete_after(), None);
+        assert!(!cfg.answer.placeholder);
+        assert_eq!(cfg.answer.profile("ask"), AnswerProfile::Conversational);
+        assert_eq!(cfg.answer.profile("search"), AnswerProfile::Structured);
+        assert_eq!(cfg.answer.profile("dollar"), AnswerProfile::Terse);
+        assert!(cfg.moderation.denylist.is_empty());
+        assert_eq!(cfg.moderation.refusal, "I can't help with that request.");
+        assert_eq!(cfg.http.pool_idle_timeout_secs, 90);
+        assert_eq!(cfg.http.pool_max_idle_per_host, 16);
 
         unsafe {
             env::remove_var("DATABASE_URL");
@@ -176,6 +1034,128 @@ mod tests {
         }
     }
 
+    #[test]
+    #[serial]
+    fn env_number_validates_range() {
+        unsafe {
+            env::remove_var("TEST_ENV_NUMBER");
+        }
+        assert_eq!(env_number("TEST_ENV_NUMBER", 90u64, 1..=3600).unwrap(), 90);
+
+        unsafe {
+            env::set_var("TEST_ENV_NUMBER", " 30 ");
+        }
+        assert_eq!(env_number("TEST_ENV_NUMBER", 90u64, 1..=3600).unwrap(), 30);
+
+        for bad in ["0", "3601", "-5", "soon"] {
+            unsafe {
+                env::set_var("TEST_ENV_NUMBER", bad);
+            }
+            assert!(matches!(
+                env_number("TEST_ENV_NUMBER", 90u64, 1..=3600),
+                Err(ConfigError::InvalidNumber("TEST_ENV_NUMBER", _))
+            ));
+        }
+
+        unsafe {
+            env::remove_var("TEST_ENV_NUMBER");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn answer_profiles_override_command_defaults() {
+        unsafe {
+            env::set_var("ANSWER_PROFILES", " /Ask:terse, search:PLAIN ");
+        }
+        let profiles = env_answer_profiles().unwrap();
+        let cfg = AnswerConfig {
+            profiles,
+            ..AnswerConfig::default()
+        };
+        assert_eq!(cfg.profile("ask"), AnswerProfile::Terse);
+        assert_eq!(cfg.profile("search"), AnswerProfile::Plain);
+        assert_eq!(cfg.profile("say"), AnswerProfile::Conversational);
+        assert_eq!(cfg.profile("dollar"), AnswerProfile::Terse);
+
+        for bad in ["ask", "ask:fancy", ":terse"] {
+            unsafe {
+                env::set_var("ANSWER_PROFILES", bad);
+            }
+            assert!(matches!(
+                env_answer_profiles(),
+                Err(ConfigError::InvalidAnswerProfile(_))
+            ));
+        }
+
+        unsafe {
+            env::remove_var("ANSWER_PROFILES");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn system_prompt_override_sources() {
+        let path = env::temp_dir().join("tscrapingbot_system_prompt_test.md");
+        unsafe {
+            env::remove_var("SYSTEM_PROMPT_OVERRIDE");
+            env::remove_var("SYSTEM_PROMPT_FILE");
+        }
+        assert!(system_prompt_override().unwrap().is_none());
+
+        unsafe {
+            env::set_var("SYSTEM_PROMPT_OVERRIDE", "Answer like a pirate.");
+        }
+        assert_eq!(
+            system_prompt_override().unwrap().as_deref(),
+            Some("Answer like a pirate.")
+        );
+
+        std::fs::write(&path, "From a file.\n").unwrap();
+        unsafe {
+            env::set_var("SYSTEM_PROMPT_FILE", &path);
+        }
+        assert!(matches!(
+            system_prompt_override(),
+            Err(ConfigError::InvalidSystemPrompt(_))
+        ));
+
+        unsafe {
+            env::remove_var("SYSTEM_PROMPT_OVERRIDE");
+        }
+        assert_eq!(
+            system_prompt_override().unwrap().as_deref(),
+            Some("From a file.\n")
+        );
+
+        std::fs::write(&path, "  \n").unwrap();
+        assert!(system_prompt_override().is_err());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(system_prompt_override().is_err());
+
+        unsafe {
+            env::remove_var("SYSTEM_PROMPT_FILE");
+        }
+    }
+
+    #[test]
+    fn prompt_override_applies_to_search_only_when_enabled() {
+        let mut cfg = PromptConfig {
+            system_override: Some("custom".into()),
+            override_search: false,
+        };
+        assert_eq!(cfg.answer_base("embedded".into(), false), "custom");
+        assert_eq!(cfg.answer_base("embedded".into(), true), "embedded");
+
+        cfg.override_search = true;
+        assert_eq!(cfg.answer_base("embedded".into(), true), "custom");
+        assert_eq!(
+            PromptConfig::default().answer_base("embedded".into(), false),
+            "embedded"
+        );
+    }
+
     #[test]
     #[serial]
     fn from_env_missing_token() {
@@ -205,4 +1185,160 @@ mod tests {
             env::remove_var("DOTENV_DISABLE");
         }
     }
+
+    #[test]
+    #[serial]
+    fn from_env_rejects_mistyped_numbers() {
+        unsafe {
+            env::set_var("DOTENV_DISABLE", "1");
+            env::set_var("DATABASE_URL", "postgresql://dummy");
+            env::set_var("TELOXIDE_TOKEN", "tok");
+            env::set_var("SCRAPEDO_TOKEN", "scrape123");
+            env::set_var("GROQ_API_KEY", "HELLO");
+            env::set_var("HOSTING", "false");
+        }
+
+        for (name, bad) in [
+            ("MODEL_TEMPERATURE", "hot"),
+            ("CHAT_MAX_CONCURRENT", "-1"),
+            ("THINKING_BUDGET", "lots"),
+            ("COMMAND_TIMEOUT_SECS", "5m"),
+            ("HISTORY_RETENTION", "1e3"),
+        ] {
+            unsafe {
+                env::set_var(name, bad);
+            }
+            match AppConfig::from_env() {
+                Err(ConfigError::InvalidNumber(n, _)) if n == name => {}
+                other => panic!("expected InvalidNumber {name}, got {other:?}"),
+            }
+            unsafe {
+                env::remove_var(name);
+            }
+        }
+
+        unsafe {
+            env::remove_var("DATABASE_URL");
+            env::remove_var("TELOXIDE_TOKEN");
+            env::remove_var("SCRAPEDO_TOKEN");
+            env::remove_var("GROQ_API_KEY");
+            env::remove_var("HOSTING");
+            env::remove_var("DOTENV_DISABLE");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn from_env_detects_platform_webhook() {
+        unsafe {
+            env::set_var("DOTENV_DISABLE", "1");
+            env::set_var("DATABASE_URL", "postgresql://dummy");
+            env::set_var("TELOXIDE_TOKEN", "tok");
+            env::set_var("SCRAPEDO_TOKEN", "scrape123");
+            env::set_var("GROQ_API_KEY", "HELLO");
+            env::remove_var("HOSTING");
+            env::remove_var("WEBHOOK_URL");
+            env::set_var("RENDER_EXTERNAL_URL", "https://bot.onrender.com/");
+        }
+
+        let cfg = AppConfig::from_env().unwrap();
+        assert!(cfg.hosting);
+        assert_eq!(
+            cfg.webhook_url.unwrap().as_str(),
+            "https://bot.onrender.com/webhook"
+        );
+
+        // Explicit configuration takes precedence.
+        unsafe {
+            env::set_var("HOSTING", "false");
+            env::set_var("WEBHOOK_URL", "https://example.com/hook");
+        }
+        let cfg = AppConfig::from_env().unwrap();
+        assert!(!cfg.hosting);
+        assert_eq!(
+            cfg.webhook_url.unwrap().as_str(),
+            "https://example.com/hook"
+        );
+
+        // A non-HTTPS platform URL is rejected.
+        unsafe {
+            env::set_var("RENDER_EXTERNAL_URL", "http://bot.onrender.com");
+        }
+        assert!(matches!(
+            AppConfig::from_env(),
+            Err(ConfigError::InvalidWebhookUrl(_))
+        ));
+
+        unsafe {
+            env::remove_var("DATABASE_URL");
+            env::remove_var("TELOXIDE_TOKEN");
+            env::remove_var("SCRAPEDO_TOKEN");
+            env::remove_var("GROQ_API_KEY");
+            env::remove_var("HOSTING");
+            env::remove_var("WEBHOOK_URL");
+            env::remove_var("RENDER_EXTERNAL_URL");
+            env::remove_var("DOTENV_DISABLE");
+        }
+    }
+
+    #[test]
+    fn parses_model_chain() {
+        let chain =
+            parse_model_chain(" groq:openai/gpt-oss-120b , GROQ:llama-3.3-70b-versatile,").unwrap();
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[1].provider, Provider::Groq);
+        assert_eq!(chain[1].model, "llama-3.3-70b-versatile");
+
+        assert!(parse_model_chain("").unwrap().is_empty());
+        assert!(matches!(
+            parse_model_chain("openai/gpt-oss-120b"),
+            Err(ConfigError::InvalidModelChain(_))
+        ));
+        assert!(matches!(
+            parse_model_chain("gemini:gemini-2.5-flash"),
+            Err(ConfigError::InvalidModelChain(_))
+        ));
+    }
+
+    #[test]
+    fn parses_model_aliases() {
+        let aliases =
+            parse_model_aliases(" Pro=groq:openai/gpt-oss-120b, fast = groq:llama-3.1-8b-instant")
+                .unwrap();
+        assert_eq!(aliases.len(), 2);
+        assert_eq!(aliases["pro"].model, "openai/gpt-oss-120b");
+        assert_eq!(aliases["fast"].provider, Provider::Groq);
+
+        assert!(parse_model_aliases("").unwrap().is_empty());
+        for bad in [
+            "pro",
+            "pro=llama",
+            "=groq:llama",
+            "p r=groq:llama",
+            "pro=gemini:x",
+        ] {
+            assert!(matches!(
+                parse_model_aliases(bad),
+                Err(ConfigError::InvalidModelAlias(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn denylist_matches_prompts() {
+        let moderation = ModerationConfig {
+            denylist: parse_denylist(r" \bbuy (followers|likes)\b ; credit card, dump ;").unwrap(),
+            ..ModerationConfig::default()
+        };
+        assert_eq!(moderation.denylist.len(), 2);
+        assert!(moderation.is_denied("Where can I BUY followers cheap?"));
+        assert!(moderation.is_denied("share a credit card, dump please"));
+        assert!(!moderation.is_denied("how do followers work on Instagram?"));
+        assert!(!ModerationConfig::default().is_denied("buy followers"));
+
+        assert!(matches!(
+            parse_denylist("ok;(unclosed"),
+            Err(ConfigError::InvalidRegex("PROMPT_DENYLIST", _))
+        ));
+    }
 }
@@ -1,24 +1,30 @@
 // /ask command handler that builds context, preprocesses images, and routes prompts through LLMs.
 
 use crate::{
-    config::Models,
+    config::AppConfig,
     handlers::{
         types::MessageRow,
         utils::{
-            ChatActionKeepAlive, escape_telegram_code_entities, extract_user_info,
-            llm::{analyze_image, message_has_photo},
-            send_reply_or_plain,
+            AnswerPipeline, ChatActionKeepAlive, ResetGuard, ask_memory_enabled, extract_user_info,
+            forget_edited_turn,
+            llm::{
+                analyze_image, message_has_photo, model_error_message, reasoning_effort_for,
+                run_model_chain, suggest_thinking_budget, take_model_alias,
+            },
+            prune_history, send_answer, send_placeholder, send_reply_or_plain, settings_chat_id,
+            truncate_for_storage, with_age,
         },
     },
-    prompts::{AiPrompt, Prompt},
+    prompts::{AiPrompt, Prompt, PromptOverrides, build_system_prompt},
 };
-use groqai::{ChatMessage, GroqClient, MessageContent, Role};
+use groqai::{ChatMessage, GroqClient, Role};
 use sqlx::PgPool;
+use std::time::Duration;
 use teloxide::{
     prelude::*,
     types::{ChatAction, ThreadId},
 };
-use tracing::error;
+use tracing::{error, info};
 
 // /ask command handler that builds context, preprocesses images, and routes prompts through LLMs.
 pub async fn ask(
@@ -27,14 +33,30 @@ pub async fn ask(
     text: String,
     pool: PgPool,
     groq: GroqClient,
-    models: Models,
+    app_config: AppConfig,
 ) -> Result<(), teloxide::RequestError> {
+    let models = &app_config.models;
+
     let chat_id = msg.chat.id;
     let thread_id: Option<ThreadId> = msg.thread_id;
 
-    // Keep Telegram "typing" action alive during long processing.
-    let mut keep =
-        ChatActionKeepAlive::spawn(bot.clone(), chat_id, thread_id, ChatAction::Typing, 4);
+    // Keep Telegram "typing" action alive during long processing, unless a placeholder
+    // message stands in for it (edited commands get none, see send_placeholder).
+    let mut keep = if app_config.answer.placeholder && msg.edit_date().is_none() {
+        ChatActionKeepAlive::idle()
+    } else {
+        ChatActionKeepAlive::spawn(bot.clone(), chat_id, thread_id, ChatAction::Typing, 4)
+    };
+
+    // A leading "[alias]" picks one of the configured models for this question.
+    let (text, alias_model) = match take_model_alias(&text, &models.aliases) {
+        Ok(v) => v,
+        Err(err_msg) => {
+            keep.shutdown().await;
+            send_reply_or_plain(&bot, &msg, err_msg, false, false, None).await?;
+            return Ok(());
+        }
+    };
 
     if text.trim().is_empty() {
         keep.shutdown().await;
@@ -44,74 +66,141 @@ pub async fn ask(
             "I can't reply to an empty message. Use /ask <query>.",
             false,
             false,
+            None,
         )
         .await?;
         return Ok(());
     }
 
+    // Stands in for the answer where "typing" isn't shown; the first reply below replaces it.
+    if app_config.answer.placeholder {
+        send_placeholder(&bot, &msg).await;
+    }
+
     // Prompt helper to access predefined system prompts.
     let prompts = AiPrompt::new();
 
-    let (user_id, user_lang, msg_chat_id) = match extract_user_info(&msg) {
+    let (user_id, user_lang, msg_chat_id) = match extract_user_info(&msg, &app_config.identity) {
         Ok(v) => v,
         Err(err_msg) => {
             // User-facing error, stop typing indicator, return
             keep.shutdown().await;
-            send_reply_or_plain(&bot, &msg, err_msg, false, false).await?;
+            send_reply_or_plain(&bot, &msg, err_msg, false, false, None).await?;
             return Ok(());
         }
     };
 
+    // Detects a /reset that runs while this command is still working.
+    let reset_guard = ResetGuard::start(user_id, msg_chat_id);
+
+    // /memory off makes /ask stateless in this chat: no history is loaded or saved.
+    // The setting lives on the chat; history itself stays grouped per forum topic.
+    let memory = match ask_memory_enabled(&pool, settings_chat_id(&msg)).await {
+        Ok(enabled) => enabled,
+        Err(e) => {
+            error!("Reading memory setting failed, keeping memory on: {e}");
+            true
+        }
+    };
+
+    // An edited command replaces its earlier answer, so the turn saved by that run goes
+    // too and doesn't come back as context for the re-run.
+    if msg.edit_date().is_some() {
+        match forget_edited_turn(&pool, user_id, msg_chat_id, i64::from(msg.id.0)).await {
+            Ok(0) => {}
+            Ok(n) => info!("Dropped {n} history rows saved before the command was edited"),
+            Err(e) => error!("Dropping the edited command's turn failed: {e}"),
+        }
+    }
+
     // Load recent messages using your stored procedure.
     let history_limit: i32 = 30;
-    let mut messages: Vec<MessageRow> = match sqlx::query_as!(
-        MessageRow,
-        "SELECT content, ia_response FROM get_recent_messages($1, $2, $3, $4)",
-        user_lang,
-        user_id,
-        msg_chat_id,
-        history_limit,
-    )
-    .fetch_all(&pool)
-    .await
-    {
-        Ok(rows) => rows,
-        Err(e) => {
-            error!("Query failed: {e}");
-            keep.shutdown().await;
-            send_reply_or_plain(&bot, &msg, "Database error.", false, false).await?;
-            return Ok(());
+    let mut messages: Vec<MessageRow> = if !memory {
+        Vec::new()
+    } else {
+        match sqlx::query_as!(
+            MessageRow,
+            "SELECT content, ia_response, age_secs FROM get_recent_messages($1, $2, $3, $4)",
+            user_lang,
+            user_id,
+            msg_chat_id,
+            history_limit,
+        )
+        .fetch_all(&pool)
+        .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                error!("Query failed: {e}");
+                keep.shutdown().await;
+                send_reply_or_plain(
+                    &bot,
+                    &msg,
+                    "Database error.",
+                    false,
+                    false,
+                    app_config.answer.error_delete_after(),
+                )
+                .await?;
+                return Ok(());
+            }
         }
     };
     messages.reverse();
 
     let image_section = if message_has_photo(&msg) {
-        analyze_image(
+        match analyze_image(
             &bot,
             &msg,
             &text,
             &prompts.get(Prompt::Vision),
             messages.clone(),
             &groq,
-            &models.clone().vision,
+            models,
         )
         .await
+        {
+            Ok(section) => section,
+            Err(err_msg) => {
+                keep.shutdown().await;
+                send_reply_or_plain(
+                    &bot,
+                    &msg,
+                    err_msg,
+                    false,
+                    false,
+                    app_config.answer.error_delete_after(),
+                )
+                .await?;
+                return Ok(());
+            }
+        }
     } else {
         String::new()
     };
 
-    let main_model = &models.thinking;
-
     // Build conversation messages: system prompt, previous turns (user -> assistant), then current user message.
-    let system_prompt = prompts.get(Prompt::ThinkAndFormat);
+    let system_prompt = build_system_prompt(
+        &app_config
+            .prompt
+            .answer_base(prompts.get(Prompt::ThinkAndFormat), false),
+        &user_lang,
+        &PromptOverrides::from_config(&app_config.answer),
+    );
 
     let mut convo: Vec<ChatMessage> = Vec::new();
     convo.push(ChatMessage::new_text(Role::System, system_prompt));
 
     // Append historical turns (if any). For each saved row: user content then assistant response.
+    // With HISTORY_TIMESTAMPS each turn says how long ago it was, for recency.
+    let timestamps = app_config.history.timestamps;
     for row in &messages {
         if let Some(ref user_content) = row.content {
-            convo.push(ChatMessage::new_text(Role::User, user_content.clone()));
+            let age = row.age_secs.filter(|_| timestamps);
+            convo.push(ChatMessage::new_text(
+                Role::User,
+                with_age(user_content, age),
+            ));
         }
         if let Some(ref assistant_content) = row.ia_response {
             convo.push(ChatMessage::new_text(
@@ -123,64 +212,106 @@ pub async fn ask(
 
     // Current user message: include image_section if present.
     let current_user_msg = if image_section.is_empty() {
-        format!(
-            "Main lang is \"{user_lang}\":\n\nOriginal prompt: {}\n",
-            text
-        )
+        format!("Original prompt: {}\n", text)
     } else {
         format!(
-            "Main lang is \"{user_lang}\":\n\nOriginal prompt: {}\n\nImage analysis:\n{}\n",
+            "Original prompt: {}\n\nImage analysis:\n{}\n",
             text, image_section
         )
     };
     convo.push(ChatMessage::new_text(Role::User, current_user_msg));
 
-    // Call the main model directly with the conversation (no intermediate reasoning step).
-    let resp = match groq
-        .chat(main_model)
-        .messages(convo)
-        .max_completion_tokens(3000)
-        .temperature(0.0)
-        .send()
-        .await
+    // Reasoning tokens count against the completion limit, so the budget is added on top
+    // of the answer allowance and also picks the reasoning effort that bounds them;
+    // an explicit THINKING_BUDGET wins over the heuristic.
+    let thinking_budget = models
+        .thinking_budget
+        .unwrap_or_else(|| suggest_thinking_budget(&text));
+    let max_tokens = 3000 + thinking_budget.max(0) as u32;
+    let reasoning_effort = reasoning_effort_for(thinking_budget);
+
+    // Call the configured model chain directly with the conversation (no intermediate reasoning step).
+    let chain = match &alias_model {
+        Some(model) => std::slice::from_ref(model),
+        None => models.ask_chain.as_slice(),
+    };
+    let (raw_answer, answered_by) = match run_model_chain(
+        &groq,
+        chain,
+        convo,
+        max_tokens,
+        models.temperature,
+        Some(reasoning_effort),
+        Duration::from_secs(models.text_timeout_secs),
+    )
+    .await
     {
         Ok(r) => r,
         Err(e) => {
             // Model error
             keep.shutdown().await;
-            send_reply_or_plain(&bot, &msg, format!("Error: {e}."), false, false).await?;
+            send_reply_or_plain(
+                &bot,
+                &msg,
+                model_error_message(&e),
+                false,
+                false,
+                app_config.answer.error_delete_after(),
+            )
+            .await?;
             return Ok(());
         }
     };
 
-    // Extract textual content (same logic you had in helpers).
-    let raw_answer = if let MessageContent::Text(text) = &resp.choices[0].message.content {
-        text.trim().to_string()
-    } else {
-        String::new()
-    };
-
     // Escape for Telegram HTML before sending and saving.
-    let final_answer = escape_telegram_code_entities(&raw_answer);
+    let answer_pipeline =
+        AnswerPipeline::for_profile(&app_config.answer, app_config.answer.profile("ask"));
+    let final_answer = answer_pipeline.apply(&raw_answer);
 
     keep.shutdown().await;
 
-    let send_req = send_reply_or_plain(&bot, &msg, final_answer.clone(), false, true);
+    // The model note is only shown, not saved into the history.
+    let reply = if models.show_ask_model {
+        format!(
+            "<i>Model: {}</i>\n\n{final_answer}",
+            html_escape::encode_text(&answered_by)
+        )
+    } else {
+        final_answer.clone()
+    };
+    let send_req = send_answer(&bot, &msg, reply, &app_config.answer);
 
     if let Err(e) = send_req.await {
         error!("Telegram send failed: {e} — no DB transaction to roll back.");
         return Ok(());
     }
 
+    if !memory {
+        return Ok(());
+    }
+
+    // The history was reset meanwhile: don't bring this turn back into it.
+    if reset_guard.reset_since_start() {
+        info!("History reset during the command, not saving this turn");
+        return Ok(());
+    }
+
+    // Cut on char boundaries so oversized rows never store broken UTF-8.
+    let max_chars = app_config.history.max_chars;
+    let stored_content = truncate_for_storage(&text, max_chars, "content");
+    let stored_answer = truncate_for_storage(&final_answer, max_chars, "ia_response");
+
     if let Err(e) = sqlx::query!(
         r#"
-        INSERT INTO messages (user_telegram_id, chat_telegram_id, content, ia_response)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO messages
+          (user_telegram_id, chat_telegram_id, content, ia_response, message_telegram_id)
+        VALUES ($1, $2, $3, $4, $5)
         "#,
         user_id,
         msg_chat_id,
-        text,
-        final_answer,
+        stored_content,
+        stored_answer,
+        i64::from(msg.id.0),
     )
     .execute(&pool)
     .await
@@ -192,8 +323,19 @@ pub async fn ask(
             "Database error (couldn't save message).",
             false,
             false,
+            app_config.answer.error_delete_after(),
         )
         .await?;
+        return Ok(());
+    }
+
+    // Retention policy: keep only the newest rows for this user/chat.
+    if app_config.history.retention > 0 {
+        match prune_history(&pool, user_id, msg_chat_id, app_config.history.retention).await {
+            Ok(0) => {}
+            Ok(pruned) => info!("Pruned {pruned} old history rows"),
+            Err(e) => error!("History pruning failed: {e}"),
+        }
     }
 
     Ok(())
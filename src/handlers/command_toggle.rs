@@ -0,0 +1,109 @@
+// Handler for /enable and /disable: chat admins switch single commands on or off.
+
+use crate::{
+    commands::Command,
+    config::AppConfig,
+    handlers::utils::{
+        send_reply_or_plain, sender_is_chat_admin, set_command_disabled, settings_chat_id,
+    },
+};
+use sqlx::PgPool;
+use teloxide::{prelude::*, utils::command::BotCommands};
+use tracing::{error, info};
+
+// Never disabled, so admins can always undo a /disable.
+const ALWAYS_ENABLED: [&str; 2] = ["enable", "disable"];
+
+// Normalize "/Search@MyBot" to "search" and check it is a command that can be switched.
+pub fn parse_command_name(text: &str) -> Result<String, String> {
+    let name = text
+        .trim()
+        .trim_start_matches('/')
+        .split('@')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if name.is_empty() {
+        return Err("Name the command, e.g. /disable search.".to_string());
+    }
+
+    let known = Command::bot_commands()
+        .iter()
+        .any(|c| c.command.trim_start_matches('/') == name);
+    if !known {
+        return Err(format!("There is no /{name} command."));
+    }
+    if ALWAYS_ENABLED.contains(&name.as_str()) {
+        return Err(format!("/{name} can't be disabled."));
+    }
+    Ok(name)
+}
+
+pub async fn toggle_command(
+    bot: Bot,
+    msg: Message,
+    text: String,
+    pool: PgPool,
+    app_config: AppConfig,
+    disable: bool,
+) -> Result<(), teloxide::RequestError> {
+    let name = match parse_command_name(&text) {
+        Ok(name) => name,
+        Err(err_msg) => {
+            send_reply_or_plain(&bot, &msg, err_msg, false, false, None).await?;
+            return Ok(());
+        }
+    };
+
+    if !sender_is_chat_admin(&bot, &msg).await {
+        send_reply_or_plain(
+            &bot,
+            &msg,
+            "Only chat admins can enable or disable commands.",
+            false,
+            false,
+            None,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    // Applies to the whole chat, every forum topic included.
+    let chat_id = settings_chat_id(&msg);
+    let (reply, delete_after) = match set_command_disabled(&pool, chat_id, &name, disable).await {
+        Ok(()) => {
+            info!("/{name} disabled={disable} in chat {chat_id}");
+            let state = if disable { "disabled" } else { "enabled" };
+            (format!("/{name} is now {state} in this chat."), None)
+        }
+        Err(e) => {
+            error!("Saving command toggle failed: {e}");
+            (
+                "Database error (couldn't save the setting).".to_string(),
+                app_config.answer.error_delete_after(),
+            )
+        }
+    };
+    send_reply_or_plain(&bot, &msg, reply, false, false, delete_after).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_command_name;
+
+    #[test]
+    fn normalizes_command_names() {
+        assert_eq!(parse_command_name("search").unwrap(), "search");
+        assert_eq!(parse_command_name(" /Say@MyBot ").unwrap(), "say");
+    }
+
+    #[test]
+    fn rejects_unknown_and_protected_commands() {
+        assert!(parse_command_name("").is_err());
+        assert!(parse_command_name("/nope").unwrap_err().contains("/nope"));
+        assert!(parse_command_name("disable").is_err());
+        assert!(parse_command_name("/enable").is_err());
+    }
+}
@@ -1,18 +1,110 @@
 // Fetches the current dollar price from the BCV website.
 
-use crate::handlers::utils::{ChatActionKeepAlive, send_reply_or_plain};
+use crate::{
+    config::{AnswerProfile, DollarConfig},
+    handlers::utils::{ChatActionKeepAlive, html_to_plain_text, send_reply_or_plain},
+};
 use kuchiki::traits::*;
+use once_cell::sync::Lazy;
 use regex::Regex;
 use reqwest;
-use std::time::Duration;
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
 use teloxide::{
     prelude::*,
     types::{ChatAction, ThreadId},
 };
-use tracing::error;
+use tracing::{error, info, warn};
+
+// A price as BCV prints it, with an optional decimal part.
+static PRICE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\d+(?:[.,]\d+)*").unwrap());
+
+// BCV fetch state shared by every chat.
+#[derive(Default)]
+struct PriceState {
+    // Last fetched price and when it was fetched.
+    price: Option<(Instant, f64)>,
+    // Last fetch attempt and its error, if it failed.
+    attempt: Option<(Instant, Option<&'static str>)>,
+}
+
+impl PriceState {
+    // The cached price if it is younger than `ttl`; a zero `ttl` disables the cache.
+    fn fresh_price(&self, ttl: Duration) -> Option<f64> {
+        self.price
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < ttl)
+            .map(|(_, price)| price)
+    }
+}
+
+// Async lock: it is held during the fetch so concurrent /dollar calls share one request.
+static PRICE_STATE: Lazy<tokio::sync::Mutex<PriceState>> =
+    Lazy::new(|| tokio::sync::Mutex::new(PriceState::default()));
+
+// Return the price cached within `ttl`, or fetch it with `fetch`. Callers arriving during a
+// fetch wait for it and reuse its outcome, and attempts are at least `min_interval` apart:
+// within it the last outcome is returned again, even a failure.
+async fn shared_price<F, Fut>(
+    state: &tokio::sync::Mutex<PriceState>,
+    ttl: Duration,
+    min_interval: Duration,
+    fetch: F,
+) -> Result<f64, &'static str>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<f64, &'static str>>,
+{
+    let mut state = state.lock().await;
+    if let Some(price) = state.fresh_price(ttl) {
+        return Ok(price);
+    }
+    if let Some((attempted_at, err)) = state.attempt
+        && attempted_at.elapsed() < min_interval
+    {
+        info!(
+            "BCV fetched {}s ago, reusing its outcome",
+            attempted_at.elapsed().as_secs()
+        );
+        return match err {
+            Some(err) => Err(err),
+            None => state
+                .price
+                .map(|(_, price)| price)
+                .ok_or("Failed to get BCV dollar value."),
+        };
+    }
+
+    let result = fetch().await;
+    let now = Instant::now();
+    state.attempt = Some((now, result.err()));
+    if let Ok(price) = result {
+        state.price = Some((now, price));
+    }
+    result
+}
 
 // Handles the /dollar command, retrieves price and sends reply.
-pub async fn dollar(bot: Bot, msg: Message, text: String) -> Result<(), teloxide::RequestError> {
+pub async fn dollar(
+    bot: Bot,
+    msg: Message,
+    text: String,
+    cfg: DollarConfig,
+    profile: AnswerProfile,
+    error_delete_after: Option<Duration>,
+) -> Result<(), teloxide::RequestError> {
+    // Cache hit: the answer is ready, so skip the typing indicator entirely. A locked
+    // state means a fetch is in flight: wait for it below.
+    let ttl = Duration::from_secs(cfg.cache_ttl_secs);
+    if let Some(price) = PRICE_STATE
+        .try_lock()
+        .ok()
+        .and_then(|state| state.fresh_price(ttl))
+    {
+        return reply_with_price(&bot, &msg, &text, price, profile).await;
+    }
+
     let chat_id = msg.chat.id;
     let thread_id: Option<ThreadId> = msg.thread_id;
 
@@ -20,6 +112,23 @@ pub async fn dollar(bot: Bot, msg: Message, text: String) -> Result<(), teloxide
     let mut keep =
         ChatActionKeepAlive::spawn(bot.clone(), chat_id, thread_id, ChatAction::Typing, 4);
 
+    let min_interval = Duration::from_secs(cfg.min_interval_secs);
+    let price = match shared_price(&PRICE_STATE, ttl, min_interval, || fetch_bcv_price(&cfg)).await
+    {
+        Ok(price) => price,
+        Err(user_msg) => {
+            keep.shutdown().await;
+            send_reply_or_plain(&bot, &msg, user_msg, false, false, error_delete_after).await?;
+            return Ok(());
+        }
+    };
+
+    keep.shutdown().await;
+    reply_with_price(&bot, &msg, &text, price, profile).await
+}
+
+// Scrape the BCV homepage; errors are returned as user-facing messages.
+async fn fetch_bcv_price(cfg: &DollarConfig) -> Result<f64, &'static str> {
     let client = match reqwest::Client::builder()
         .danger_accept_invalid_certs(true)
         .timeout(Duration::from_secs(10))
@@ -27,9 +136,8 @@ pub async fn dollar(bot: Bot, msg: Message, text: String) -> Result<(), teloxide
     {
         Ok(c) => c,
         Err(e) => {
-            keep.shutdown().await;
             error!("Failed to build reqwest client: {e}");
-            return Ok(());
+            return Err("Failed to get BCV dollar value.");
         }
     };
 
@@ -37,17 +145,8 @@ pub async fn dollar(bot: Bot, msg: Message, text: String) -> Result<(), teloxide
     let res = match client.get("https://www.bcv.org.ve").send().await {
         Ok(val) => val,
         Err(e) => {
-            keep.shutdown().await;
             error!("Could not retrieve the dollar page: {:?}", e);
-            send_reply_or_plain(
-                &bot,
-                &msg,
-                "Could not retrieve the dollar page (Connection Error).",
-                false,
-                false,
-            )
-            .await?;
-            return Ok(());
+            return Err("Could not retrieve the dollar page (Connection Error).");
         }
     };
 
@@ -55,97 +154,242 @@ pub async fn dollar(bot: Bot, msg: Message, text: String) -> Result<(), teloxide
     let raw = match res.text().await {
         Ok(val) => val,
         Err(e) => {
-            // Handle text conversion failure.
-            keep.shutdown().await;
             error!("Could not convert the response to text: {e}");
-            send_reply_or_plain(
-                &bot,
-                &msg,
-                "Could not convert the response to text.",
-                false,
-                false,
-            )
-            .await?;
-            return Ok(());
+            return Err("Could not convert the response to text.");
         }
     };
 
-    // Parse HTML and extract dollar price.
-    let dollar_price_opt: Option<f64> = {
-        let document = kuchiki::parse_html().one(raw);
-
-        // Select strong element inside #dolar.
-        match document.select("#dolar strong") {
-            Ok(mut nodes) => {
-                if let Some(node_ref) = nodes.next() {
-                    let text = node_ref.as_node().text_contents();
-                    // Clean and parse price string.
-                    match text.trim().replace(",", ".").parse::<f64>() {
-                        Ok(val) => Some(val),
-                        Err(e) => {
-                            error!("Failed to convert the value to a number: {e}");
-                            None
-                        }
-                    }
-                } else {
-                    None
+    parse_bcv_price(&raw, &cfg.selectors).ok_or("Failed to get BCV dollar value.")
+}
+
+// Extract the price trying each configured selector first, then a search for the
+// number next to a "USD"/"Dólar" label. Logs which strategy matched.
+pub fn parse_bcv_price(html: &str, selectors: &[String]) -> Option<f64> {
+    let document = kuchiki::parse_html().one(html);
+
+    for selector in selectors {
+        match document.select_first(selector) {
+            Ok(node_ref) => {
+                let text = node_ref.as_node().text_contents();
+                if let Some(price) = parse_price_number(&text) {
+                    info!("BCV price matched selector {selector:?}");
+                    return Some(price);
                 }
+                warn!(
+                    "BCV selector {selector:?} matched non-numeric text: {:?}",
+                    text.trim()
+                );
             }
-            Err(e) => {
-                error!("Failed to run CSS selector on document: {:?}", e);
-                None
+            Err(_) => warn!("BCV selector {selector:?} matched nothing"),
+        }
+    }
+
+    // Fallback: a text node labelled USD/Dólar, with the price in a nearby ancestor.
+    for text_node in document.inclusive_descendants().text_nodes() {
+        let label = text_node.borrow().trim().to_lowercase();
+        if !(label == "usd" || label.starts_with("dólar") || label.starts_with("dolar")) {
+            continue;
+        }
+
+        for ancestor in text_node.as_node().ancestors().take(3) {
+            let text = ancestor.text_contents();
+            if let Some(price) = PRICE_RE
+                .find_iter(&text)
+                .find_map(|m| parse_price_number(m.as_str()))
+            {
+                info!("BCV price matched label fallback ({label:?})");
+                return Some(price);
             }
         }
+    }
+
+    error!("No BCV price strategy matched");
+    None
+}
+
+// Parse a BCV-formatted number ("36,43120000" or "1.036,43") into f64.
+fn parse_price_number(text: &str) -> Option<f64> {
+    let raw = PRICE_RE.find(text.trim())?.as_str();
+    let normalized = if raw.contains(',') {
+        raw.replace('.', "").replace(',', ".")
+    } else {
+        raw.to_string()
     };
+    normalized.parse::<f64>().ok().filter(|p| *p > 0.0)
+}
 
-    // Send appropriate reply based on extraction result.
-    match dollar_price_opt {
-        Some(dollar_price) => {
-            if text.is_empty() {
-                let message = format!("<b>BCV</b>: <code>{dollar_price} Bs.</code>");
-                keep.shutdown().await;
-                send_reply_or_plain(&bot, &msg, message, false, true).await?;
-                Ok(())
-            } else {
-                let re_currency = Regex::new(r"(?i)(\d+(?:\.\d+)?)\s*(bs|\$)").unwrap();
-                if let Some(caps) = re_currency.captures(&text) {
-                    let amount: f64 = caps[1].parse().unwrap_or(0.0);
-                    let currency = caps[2].to_lowercase();
-
-                    let (converted, target_currency) = if currency == "bs" {
-                        // Bs to $
-                        (amount / dollar_price, "$")
-                    } else {
-                        // $ to Bs
-                        (amount * dollar_price, "Bs")
-                    };
-
-                    let message = format!(
-                        "<b>BCV</b>: <code>{:.2} {}</code>",
-                        converted, target_currency
-                    );
-                    keep.shutdown().await;
-                    send_reply_or_plain(&bot, &msg, message, false, true).await?;
-                } else {
-                    let re_number = Regex::new(r"\d+(?:\.\d+)?").unwrap();
-                    if re_number.is_match(&text) {
-                        let error_msg = "Please specify the currency (Bs or $) along with the amount, e.g., '10 Bs' or '10 $'.";
-                        keep.shutdown().await;
-                        send_reply_or_plain(&bot, &msg, error_msg, false, false).await?;
-                    } else {
-                        let message = format!("<b>BCV</b>: <code>{dollar_price} Bs</code>.");
-                        keep.shutdown().await;
-                        send_reply_or_plain(&bot, &msg, message, false, true).await?;
-                    }
-                }
-                Ok(())
-            }
+// Send the price, or the conversion requested in `text`.
+async fn reply_with_price(
+    bot: &Bot,
+    msg: &Message,
+    text: &str,
+    dollar_price: f64,
+    profile: AnswerProfile,
+) -> Result<(), teloxide::RequestError> {
+    // The plain profile sends the figures without formatting.
+    let send_price = |message: String| async move {
+        if profile.html() {
+            send_reply_or_plain(bot, msg, message, false, true, None).await
+        } else {
+            send_reply_or_plain(bot, msg, html_to_plain_text(&message), false, false, None).await
         }
-        None => {
-            keep.shutdown().await;
-            send_reply_or_plain(&bot, &msg, "Failed to get BCV dollar value.", false, false)
-                .await?;
-            Ok(())
+    };
+
+    if text.is_empty() {
+        send_price(format!("<b>BCV</b>: <code>{dollar_price} Bs.</code>")).await?;
+        return Ok(());
+    }
+
+    let re_currency = Regex::new(r"(?i)(\d+(?:\.\d+)?)\s*(bs|\$)").unwrap();
+    if let Some(caps) = re_currency.captures(text) {
+        let amount: f64 = caps[1].parse().unwrap_or(0.0);
+        let currency = caps[2].to_lowercase();
+
+        let (converted, target_currency) = if currency == "bs" {
+            // Bs to $
+            (amount / dollar_price, "$")
+        } else {
+            // $ to Bs
+            (amount * dollar_price, "Bs")
+        };
+
+        send_price(format!(
+            "<b>BCV</b>: <code>{:.2} {}</code>",
+            converted, target_currency
+        ))
+        .await?;
+    } else {
+        let re_number = Regex::new(r"\d+(?:\.\d+)?").unwrap();
+        if re_number.is_match(text) {
+            let error_msg = "Please specify the currency (Bs or $) along with the amount, e.g., '10 Bs' or '10 $'.";
+            send_reply_or_plain(bot, msg, error_msg, false, false, None).await?;
+        } else {
+            send_price(format!("<b>BCV</b>: <code>{dollar_price} Bs</code>.")).await?;
         }
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PriceState, parse_bcv_price, shared_price};
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    const CURRENT_LAYOUT: &str = r#"
+        <div class="row recuadrotsmc" id="dolar">
+          <div class="col-sm-6 col-xs-6"><img src="usd.png"/><span> USD</span></div>
+          <div class="col-sm-6 col-xs-6 centrado"><strong> 36,43120000 </strong></div>
+        </div>"#;
+
+    const ALTERNATE_LAYOUT: &str = r#"
+        <section class="tasas">
+          <div class="tasa"><span>EUR</span><span>39,10</span></div>
+          <div class="tasa"><span>USD</span><span>1.036,50</span></div>
+        </section>"#;
+
+    fn default_selectors() -> Vec<String> {
+        vec!["#dolar strong".to_string()]
+    }
+
+    #[test]
+    fn parses_current_layout_with_default_selector() {
+        assert_eq!(
+            parse_bcv_price(CURRENT_LAYOUT, &default_selectors()),
+            Some(36.4312)
+        );
+    }
+
+    #[test]
+    fn falls_back_to_usd_label_on_alternate_layout() {
+        assert_eq!(
+            parse_bcv_price(ALTERNATE_LAYOUT, &default_selectors()),
+            Some(1036.5)
+        );
+    }
+
+    #[test]
+    fn configured_selector_takes_precedence() {
+        let selectors = vec![".tasa:first-child span:last-child".to_string()];
+        assert_eq!(parse_bcv_price(ALTERNATE_LAYOUT, &selectors), Some(39.1));
+    }
+
+    #[test]
+    fn returns_none_without_any_price() {
+        assert_eq!(
+            parse_bcv_price("<p>Sin datos</p>", &default_selectors()),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_calls_share_one_fetch() {
+        let state = tokio::sync::Mutex::new(PriceState::default());
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(36.43)
+        };
+        let window = Duration::from_secs(60);
+
+        let (a, b, c) = tokio::join!(
+            shared_price(&state, Duration::ZERO, window, fetch),
+            shared_price(&state, Duration::ZERO, window, fetch),
+            shared_price(&state, Duration::ZERO, window, fetch),
+        );
+        assert_eq!((a, b, c), (Ok(36.43), Ok(36.43), Ok(36.43)));
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn failures_are_not_retried_within_the_interval() {
+        let state = tokio::sync::Mutex::new(PriceState::default());
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Err("Could not retrieve the dollar page (Connection Error).")
+        };
+
+        let window = Duration::from_secs(60);
+        let ttl = Duration::from_secs(600);
+        assert!(shared_price(&state, ttl, window, fetch).await.is_err());
+        assert!(shared_price(&state, ttl, window, fetch).await.is_err());
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        // Without an interval every call may retry.
+        assert!(
+            shared_price(&state, ttl, Duration::ZERO, fetch)
+                .await
+                .is_err()
+        );
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn prices_are_cached_only_with_a_ttl() {
+        let state = tokio::sync::Mutex::new(PriceState::default());
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(36.43)
+        };
+
+        // A zero TTL (the default) fetches every time once the interval is over.
+        for _ in 0..2 {
+            let price = shared_price(&state, Duration::ZERO, Duration::ZERO, fetch).await;
+            assert_eq!(price, Ok(36.43));
+        }
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+        let ttl = Duration::from_secs(600);
+        assert_eq!(
+            shared_price(&state, ttl, Duration::ZERO, fetch).await,
+            Ok(36.43)
+        );
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
 }
@@ -0,0 +1,128 @@
+// Handler for the /forget command: drops only the most recent turns of the history.
+
+use crate::{
+    config::AppConfig,
+    handlers::utils::{ChatActionKeepAlive, extract_user_info, send_reply_or_plain},
+};
+use sqlx::PgPool;
+use teloxide::{
+    prelude::*,
+    types::{ChatAction, ThreadId},
+};
+use tracing::error;
+
+// Turns to forget: 1 when no argument is given, None for anything but a positive integer.
+pub fn parse_forget_count(text: &str) -> Option<i64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Some(1);
+    }
+    text.parse::<i64>().ok().filter(|n| *n > 0)
+}
+
+pub async fn forget(
+    bot: Bot,
+    msg: Message,
+    text: String,
+    pool: PgPool,
+    app_config: AppConfig,
+) -> Result<(), teloxide::RequestError> {
+    let chat_id = msg.chat.id;
+    let thread_id: Option<ThreadId> = msg.thread_id;
+
+    let Some(count) = parse_forget_count(&text) else {
+        send_reply_or_plain(
+            &bot,
+            &msg,
+            "Use /forget [N] with a positive number of messages (default 1).",
+            false,
+            false,
+            None,
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let mut keep =
+        ChatActionKeepAlive::spawn(bot.clone(), chat_id, thread_id, ChatAction::Typing, 4);
+
+    let (user_id, _, msg_chat_id) = match extract_user_info(&msg, &app_config.identity) {
+        Ok(v) => v,
+        Err(err_msg) => {
+            keep.shutdown().await;
+            send_reply_or_plain(&bot, &msg, err_msg, false, false, None).await?;
+            return Ok(());
+        }
+    };
+
+    match sqlx::query!(
+        r#"
+        UPDATE messages
+        SET deleted_at = now()
+        WHERE id IN (
+          SELECT id
+          FROM messages
+          WHERE user_telegram_id = $1
+            AND chat_telegram_id = $2
+            AND deleted_at IS NULL
+            AND is_cleared = FALSE
+          ORDER BY created_at DESC, id DESC
+          LIMIT $3
+        )
+        "#,
+        user_id,
+        msg_chat_id,
+        count
+    )
+    .execute(&pool)
+    .await
+    {
+        Ok(res) => {
+            let affected = res.rows_affected();
+            keep.shutdown().await;
+
+            let text = match affected {
+                0 => "There is nothing to forget.".to_string(),
+                1 => "Forgot the last message.".to_string(),
+                n => format!("Forgot the last {n} messages."),
+            };
+            send_reply_or_plain(&bot, &msg, text, false, false, None).await?;
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to forget messages: {e}");
+            let err_text = "Internal database error while forgetting messages.";
+            keep.shutdown().await;
+
+            send_reply_or_plain(
+                &bot,
+                &msg,
+                err_text,
+                false,
+                false,
+                app_config.answer.error_delete_after(),
+            )
+            .await?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_forget_count;
+
+    #[test]
+    fn defaults_to_one() {
+        assert_eq!(parse_forget_count(""), Some(1));
+        assert_eq!(parse_forget_count("  "), Some(1));
+    }
+
+    #[test]
+    fn accepts_only_positive_numbers() {
+        assert_eq!(parse_forget_count(" 3 "), Some(3));
+        assert_eq!(parse_forget_count("0"), None);
+        assert_eq!(parse_forget_count("-2"), None);
+        assert_eq!(parse_forget_count("all"), None);
+    }
+}
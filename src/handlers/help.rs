@@ -0,0 +1,167 @@
+// Handler for the /help command: lists commands filtered by chat type and admin status.
+
+use crate::{
+    commands::Command,
+    config::{AppConfig, HelpConfig},
+    handlers::utils::{
+        ChatScope, command_refusal, disabled_commands, send_reply_or_plain, sender_is_chat_admin,
+        settings_chat_id,
+    },
+};
+use sqlx::PgPool;
+use teloxide::{prelude::*, types::BotCommand, utils::command::BotCommands};
+use tracing::warn;
+
+const HELP_HEADER: &str = "Available commands:";
+
+// Build the help text in the same layout as `Command::descriptions()`, skipping
+// admin-only commands for non-admins, group-hidden commands in groups and the
+// `unavailable` ones (commands that would be refused here).
+pub fn build_help_text(
+    commands: &[BotCommand],
+    cfg: &HelpConfig,
+    is_group: bool,
+    is_admin: bool,
+    unavailable: &[String],
+) -> String {
+    let lines: Vec<String> = commands
+        .iter()
+        .filter(|c| {
+            let name = c.command.trim_start_matches('/').to_lowercase();
+            let admin_only = cfg.admin_only.contains(&name);
+            let group_hidden = cfg.group_hidden.contains(&name);
+            (is_admin || !admin_only)
+                && (is_admin || !is_group || !group_hidden)
+                && !unavailable.contains(&name)
+        })
+        .map(|c| format!("{} — {}", c.command, c.description))
+        .collect();
+
+    format!("{HELP_HEADER}\n\n{}", lines.join("\n"))
+}
+
+// Commands handle_command or the handler itself would refuse in this chat: blocked by the
+// chat type policy, disabled with /disable, or /say while TTS is off.
+async fn unavailable_commands(pool: &PgPool, msg: &Message, app_config: &AppConfig) -> Vec<String> {
+    let scope = ChatScope::of(&msg.chat);
+    let mut unavailable: Vec<String> = Command::bot_commands()
+        .iter()
+        .map(|c| c.command.trim_start_matches('/').to_lowercase())
+        .filter(|name| command_refusal(&app_config.chat_policy, scope, name).is_some())
+        .collect();
+
+    // /enable and /disable can't be turned off, so they stay listed.
+    match disabled_commands(pool, settings_chat_id(msg)).await {
+        Ok(disabled) => unavailable.extend(
+            disabled
+                .into_iter()
+                .filter(|name| name != "enable" && name != "disable"),
+        ),
+        Err(e) => warn!("Could not read disabled commands: {e}"),
+    }
+
+    if !app_config.tts.enabled {
+        unavailable.push("say".to_string());
+    }
+    unavailable
+}
+
+pub async fn help(
+    bot: Bot,
+    msg: Message,
+    pool: PgPool,
+    app_config: AppConfig,
+) -> Result<(), teloxide::RequestError> {
+    let cfg = &app_config.help;
+    let is_group = !msg.chat.is_private();
+
+    // Operators count as admins everywhere; in groups so do the chat's admins. A private
+    // chat has no admins of its own, so admin-only commands stay hidden there.
+    let is_operator = msg
+        .from
+        .as_ref()
+        .is_some_and(|u| app_config.is_admin(u.id.0));
+    // The admin lookup is only needed when some command is hidden from non-admins.
+    let is_admin = is_operator
+        || (is_group
+            && !(cfg.admin_only.is_empty() && cfg.group_hidden.is_empty())
+            && sender_is_chat_admin(&bot, &msg).await);
+
+    let unavailable = unavailable_commands(&pool, &msg, &app_config).await;
+    let text = build_help_text(
+        &Command::bot_commands(),
+        cfg,
+        is_group,
+        is_admin,
+        &unavailable,
+    );
+    send_reply_or_plain(&bot, &msg, text, false, false, None).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_help_text;
+    use crate::config::HelpConfig;
+    use teloxide::types::BotCommand;
+
+    fn commands() -> Vec<BotCommand> {
+        vec![
+            BotCommand::new("/ask", "respond using AI"),
+            BotCommand::new("/search", "respond using AI and your web resource"),
+            BotCommand::new("/help", "display this text."),
+        ]
+    }
+
+    fn cfg() -> HelpConfig {
+        HelpConfig {
+            group_hidden: vec!["search".into()],
+            admin_only: vec!["ask".into()],
+        }
+    }
+
+    #[test]
+    fn default_config_lists_everything() {
+        let text = build_help_text(&commands(), &HelpConfig::default(), true, false, &[]);
+        assert!(text.starts_with("Available commands:\n\n/ask — respond using AI"));
+        assert!(text.contains("/search"));
+        assert!(text.contains("/help"));
+    }
+
+    #[test]
+    fn group_members_do_not_see_hidden_or_admin_commands() {
+        let text = build_help_text(&commands(), &cfg(), true, false, &[]);
+        assert!(!text.contains("/ask"));
+        assert!(!text.contains("/search"));
+        assert!(text.contains("/help"));
+    }
+
+    #[test]
+    fn group_admins_see_everything() {
+        let text = build_help_text(&commands(), &cfg(), true, true, &[]);
+        assert!(text.contains("/ask") && text.contains("/search"));
+    }
+
+    #[test]
+    fn private_chats_ignore_group_hidden_list() {
+        let text = build_help_text(&commands(), &cfg(), false, false, &[]);
+        assert!(text.contains("/search"));
+        // Admin-only commands stay hidden for non-operators in private chats too.
+        assert!(!text.contains("/ask"));
+    }
+
+    #[test]
+    fn unavailable_commands_are_not_listed() {
+        let unavailable = vec!["search".to_string()];
+        let text = build_help_text(
+            &commands(),
+            &HelpConfig::default(),
+            false,
+            true,
+            &unavailable,
+        );
+        assert!(text.contains("/ask") && text.contains("/help"));
+        assert!(!text.contains("/search"));
+    }
+}
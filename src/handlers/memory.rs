@@ -0,0 +1,106 @@
+// Handler for the /memory command: turns /ask conversation memory on or off for a chat.
+
+use crate::{
+    config::AppConfig,
+    handlers::utils::{
+        ask_memory_enabled, send_reply_or_plain, sender_is_chat_admin, set_ask_memory,
+        settings_chat_id,
+    },
+};
+use sqlx::PgPool;
+use teloxide::prelude::*;
+use tracing::error;
+
+const USAGE: &str = "Use /memory on or /memory off.";
+
+// None shows the current setting; anything but on/off is an error.
+pub fn parse_memory_toggle(text: &str) -> Result<Option<bool>, &'static str> {
+    match text.trim().to_lowercase().as_str() {
+        "" => Ok(None),
+        "on" | "true" | "yes" | "1" => Ok(Some(true)),
+        "off" | "false" | "no" | "0" => Ok(Some(false)),
+        _ => Err(USAGE),
+    }
+}
+
+fn status_text(enabled: bool) -> &'static str {
+    if enabled {
+        "Memory is on: /ask remembers this conversation."
+    } else {
+        "Memory is off: /ask answers each message on its own."
+    }
+}
+
+pub async fn memory(
+    bot: Bot,
+    msg: Message,
+    text: String,
+    pool: PgPool,
+    app_config: AppConfig,
+) -> Result<(), teloxide::RequestError> {
+    let error_delete_after = app_config.answer.error_delete_after();
+
+    let toggle = match parse_memory_toggle(&text) {
+        Ok(t) => t,
+        Err(usage) => {
+            send_reply_or_plain(&bot, &msg, usage, false, false, None).await?;
+            return Ok(());
+        }
+    };
+
+    // The whole chat shares one setting, whichever forum topic it is changed from.
+    let chat_id = settings_chat_id(&msg);
+
+    let Some(enabled) = toggle else {
+        let (reply, delete_after) = match ask_memory_enabled(&pool, chat_id).await {
+            Ok(enabled) => (status_text(enabled), None),
+            Err(e) => {
+                error!("Reading memory setting failed: {e}");
+                ("Database error.", error_delete_after)
+            }
+        };
+        send_reply_or_plain(&bot, &msg, reply, false, false, delete_after).await?;
+        return Ok(());
+    };
+
+    // In groups the setting affects everyone, so only chat admins may change it.
+    if !sender_is_chat_admin(&bot, &msg).await {
+        send_reply_or_plain(
+            &bot,
+            &msg,
+            "Only chat admins can change the memory setting.",
+            false,
+            false,
+            None,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let (reply, delete_after) = match set_ask_memory(&pool, chat_id, enabled).await {
+        Ok(()) => (status_text(enabled), None),
+        Err(e) => {
+            error!("Saving memory setting failed: {e}");
+            (
+                "Database error (couldn't save the setting).",
+                error_delete_after,
+            )
+        }
+    };
+    send_reply_or_plain(&bot, &msg, reply, false, false, delete_after).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_memory_toggle;
+
+    #[test]
+    fn parses_memory_toggle() {
+        assert_eq!(parse_memory_toggle(""), Ok(None));
+        assert_eq!(parse_memory_toggle(" ON "), Ok(Some(true)));
+        assert_eq!(parse_memory_toggle("off"), Ok(Some(false)));
+        assert!(parse_memory_toggle("maybe").is_err());
+    }
+}
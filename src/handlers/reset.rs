@@ -1,6 +1,11 @@
 // Handler for the /reset command.
 
-use crate::handlers::utils::{ChatActionKeepAlive, send_reply_or_plain};
+use crate::{
+    config::AppConfig,
+    handlers::utils::{
+        ChatActionKeepAlive, extract_user_info, mark_history_reset, send_reply_or_plain,
+    },
+};
 use sqlx::PgPool;
 use teloxide::{
     prelude::*,
@@ -8,31 +13,26 @@ use teloxide::{
 };
 use tracing::error;
 
-pub async fn reset(bot: Bot, msg: Message, pool: PgPool) -> Result<(), teloxide::RequestError> {
+pub async fn reset(
+    bot: Bot,
+    msg: Message,
+    pool: PgPool,
+    app_config: AppConfig,
+) -> Result<(), teloxide::RequestError> {
     let chat_id = msg.chat.id;
     let thread_id: Option<ThreadId> = msg.thread_id;
 
     let mut keep =
         ChatActionKeepAlive::spawn(bot.clone(), chat_id, thread_id, ChatAction::Typing, 4);
 
-    let user = match msg.from {
-        Some(ref u) => u,
-        None => {
-            send_reply_or_plain(
-                &bot,
-                &msg,
-                "The user could not be identified.",
-                false,
-                false,
-            )
-            .await?;
+    let (user_id, _, msg_chat_id) = match extract_user_info(&msg, &app_config.identity) {
+        Ok(v) => v,
+        Err(err_msg) => {
+            send_reply_or_plain(&bot, &msg, err_msg, false, false, None).await?;
             return Ok(());
         }
     };
 
-    let user_id: i64 = user.id.0 as i64;
-    let msg_chat_id: i64 = thread_id.map(|tid| tid.0.0 as i64).unwrap_or(chat_id.0);
-
     match sqlx::query!(
         r#"
         UPDATE messages
@@ -50,14 +50,16 @@ pub async fn reset(bot: Bot, msg: Message, pool: PgPool) -> Result<(), teloxide:
     {
         Ok(res) => {
             let affected = res.rows_affected();
+            // Commands still in flight must not save their turn after this reset.
+            mark_history_reset(user_id, msg_chat_id);
             keep.shutdown().await;
 
             if affected > 0 {
                 let text = "Chat reset successfully.";
-                send_reply_or_plain(&bot, &msg, text, false, false).await?;
+                send_reply_or_plain(&bot, &msg, text, false, false, None).await?;
             } else {
                 let text = "The chat has already been reset.";
-                send_reply_or_plain(&bot, &msg, text, false, false).await?;
+                send_reply_or_plain(&bot, &msg, text, false, false, None).await?;
             }
             Ok(())
         }
@@ -66,7 +68,15 @@ pub async fn reset(bot: Bot, msg: Message, pool: PgPool) -> Result<(), teloxide:
             let err_text = "Internal database error while clearing messages.";
             keep.shutdown().await;
 
-            send_reply_or_plain(&bot, &msg, err_text, false, false).await?;
+            send_reply_or_plain(
+                &bot,
+                &msg,
+                err_text,
+                false,
+                false,
+                app_config.answer.error_delete_after(),
+            )
+            .await?;
             Ok(())
         }
     }
@@ -0,0 +1,151 @@
+// /say command handler: answers like /ask and also sends the answer as a voice message.
+
+use crate::{
+    config::AppConfig,
+    handlers::utils::{
+        AnswerPipeline, ChatActionKeepAlive, extract_user_info, html_to_plain_text,
+        llm::{model_error_message, run_main_model, synthesize_speech},
+        send_long_reply, send_reply_or_plain,
+    },
+    prompts::{AiPrompt, Prompt, PromptOverrides, build_system_prompt},
+};
+use groqai::GroqClient;
+use std::time::Duration;
+use teloxide::{
+    prelude::*,
+    types::{ChatAction, InputFile, ReplyParameters, ThreadId},
+};
+use tracing::error;
+
+pub async fn say(
+    bot: Bot,
+    msg: Message,
+    text: String,
+    groq: GroqClient,
+    app_config: AppConfig,
+) -> Result<(), teloxide::RequestError> {
+    let chat_id = msg.chat.id;
+    let thread_id: Option<ThreadId> = msg.thread_id;
+
+    if !app_config.tts.enabled {
+        send_reply_or_plain(
+            &bot,
+            &msg,
+            "Voice replies are disabled.",
+            false,
+            false,
+            None,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut keep =
+        ChatActionKeepAlive::spawn(bot.clone(), chat_id, thread_id, ChatAction::Typing, 4);
+
+    if text.trim().is_empty() {
+        keep.shutdown().await;
+        send_reply_or_plain(
+            &bot,
+            &msg,
+            "I can't reply to an empty message. Use /say <query>.",
+            false,
+            false,
+            None,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let (_, user_lang, _) = match extract_user_info(&msg, &app_config.identity) {
+        Ok(v) => v,
+        Err(err_msg) => {
+            keep.shutdown().await;
+            send_reply_or_plain(&bot, &msg, err_msg, false, false, None).await?;
+            return Ok(());
+        }
+    };
+
+    // Stateless answer: same prompt shape as /ask, without history.
+    let prompts = AiPrompt::new();
+    let prompt = format!("Original prompt: {text}\n");
+    let system_prompt = build_system_prompt(
+        &app_config
+            .prompt
+            .answer_base(prompts.get(Prompt::ThinkAndFormat), false),
+        &user_lang,
+        &PromptOverrides::from_config(&app_config.answer),
+    );
+    let raw_answer = match run_main_model(
+        &groq,
+        &prompt,
+        &app_config.models.thinking,
+        system_prompt,
+        Duration::from_secs(app_config.models.text_timeout_secs),
+    )
+    .await
+    {
+        Ok(answer) => answer,
+        Err(e) => {
+            keep.shutdown().await;
+            send_reply_or_plain(
+                &bot,
+                &msg,
+                model_error_message(&e),
+                false,
+                false,
+                app_config.answer.error_delete_after(),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let answer_pipeline =
+        AnswerPipeline::for_profile(&app_config.answer, app_config.answer.profile("say"));
+    let final_answer = answer_pipeline.apply(&raw_answer);
+
+    keep.shutdown().await;
+    if let Err(e) = send_long_reply(&bot, &msg, final_answer.clone(), true).await {
+        error!("Telegram send failed: {e}");
+        return Ok(());
+    }
+
+    // Switch the indicator to "recording voice" while the audio is synthesized.
+    let mut keep =
+        ChatActionKeepAlive::spawn(bot.clone(), chat_id, thread_id, ChatAction::RecordVoice, 4);
+
+    let audio = match synthesize_speech(&app_config.tts, &html_to_plain_text(&final_answer)).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("Speech synthesis failed: {e}");
+            keep.shutdown().await;
+            send_reply_or_plain(
+                &bot,
+                &msg,
+                "Could not create the voice message.",
+                false,
+                false,
+                app_config.answer.error_delete_after(),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let voice = InputFile::memory(audio).file_name(format!("answer.{}", app_config.tts.format));
+    let mut req = bot.send_voice(chat_id, voice);
+    if msg.chat.title().is_some() {
+        req = req.reply_parameters(ReplyParameters::new(msg.id).allow_sending_without_reply());
+        if let Some(tid) = thread_id {
+            req = req.message_thread_id(tid);
+        }
+    }
+
+    keep.shutdown().await;
+    if let Err(e) = req.await {
+        error!("Telegram voice send failed: {e}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+// /scrapetest command handler: runs the scrape pipeline alone and reports what it got.
+
+use crate::{
+    config::AppConfig,
+    handlers::utils::{
+        ChatActionKeepAlive, scrape_page, send_long_reply, send_reply_or_plain, truncate_chars,
+        truncation_indicator,
+    },
+};
+use html_escape::encode_text;
+use std::time::Instant;
+use teloxide::{
+    prelude::*,
+    types::{ChatAction, ParseMode, ThreadId},
+};
+use tracing::{error, info};
+
+// Characters of the simplified body shown in the report.
+const PREVIEW_CHARS: usize = 1500;
+
+pub async fn scrapetest(
+    bot: Bot,
+    msg: Message,
+    text: String,
+    app_config: AppConfig,
+) -> Result<(), teloxide::RequestError> {
+    let is_admin = msg
+        .from
+        .as_ref()
+        .is_some_and(|u| app_config.is_admin(u.id.0));
+    if !is_admin {
+        send_reply_or_plain(
+            &bot,
+            &msg,
+            "This command is for admins only.",
+            false,
+            false,
+            None,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let url = text.trim();
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        send_reply_or_plain(
+            &bot,
+            &msg,
+            "Use /scrapetest <url> with a valid URL (http:// or https://).",
+            false,
+            false,
+            None,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let chat_id = msg.chat.id;
+    let thread_id: Option<ThreadId> = msg.thread_id;
+    let mut keep =
+        ChatActionKeepAlive::spawn(bot.clone(), chat_id, thread_id, ChatAction::Typing, 4);
+
+    let started = Instant::now();
+    let result = scrape_page(
+        &app_config.scrapedo_token,
+        url,
+        None,
+        &app_config.scrape_direct_domains,
+    )
+    .await;
+    let elapsed = started.elapsed();
+    keep.shutdown().await;
+
+    let (fetched, via) = match result {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Scrape test failed: {e}");
+            let report = format!(
+                "<b>Scrape failed</b> after {} ms:\n<code>{}</code>",
+                elapsed.as_millis(),
+                encode_text(&e.to_string())
+            );
+            send_reply_or_plain(&bot, &msg, report, false, true, None).await?;
+            return Ok(());
+        }
+    };
+
+    info!(
+        "Scrape test for {url} took {} ms via {via}",
+        elapsed.as_millis()
+    );
+
+    let body = &fetched.page.body;
+    let report = format!(
+        "<b>Scrape test</b>\n\
+         URL: <code>{}</code>\n\
+         Via: {via}\n\
+         Time: {} ms\n\
+         Content-Type: <code>{}</code>\n\
+         Raw size: {} bytes\n\
+         Simplified size: {} bytes\n\
+         OpenGraph title: {}\n\n\
+         <b>First {PREVIEW_CHARS} chars:</b>\n<pre>{}</pre>{}",
+        encode_text(url),
+        elapsed.as_millis(),
+        encode_text(fetched.content_type.as_deref().unwrap_or("unknown")),
+        fetched.raw_len,
+        body.len(),
+        encode_text(fetched.page.metadata.title.as_deref().unwrap_or("none")),
+        encode_text(&truncate_chars(body, PREVIEW_CHARS)),
+        if body.chars().count() > PREVIEW_CHARS {
+            truncation_indicator(&app_config.answer.truncation_label, Some(ParseMode::Html))
+        } else {
+            String::new()
+        },
+    );
+
+    send_long_reply(&bot, &msg, report, true).await?;
+    Ok(())
+}
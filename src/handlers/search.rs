@@ -1,40 +1,63 @@
 // Handler for the search command
 
 use crate::{
-    config::Models,
+    config::{AppConfig, Provider},
     handlers::{
         types::MessageRow,
         utils::{
-            ChatActionKeepAlive, escape_telegram_code_entities, extract_user_info,
-            fetch_simplified_body, send_reply_or_plain,
+            AnswerPipeline, ChatActionKeepAlive, PageMetadata, PipelineStage, ResetGuard,
+            ScrapeGeo, SimplifiedPage, StageTimer, extract_user_info, forget_edited_turn,
+            llm::{
+                clamp_temperature, is_rate_limit_error, model_error_message, summarize_chunks,
+                with_model_timeout,
+            },
+            prune_history, scrape_page, scrape_pages, send_answer, send_placeholder,
+            send_reply_or_plain, split_into_chunks, truncate_for_storage, with_age,
         },
     },
-    prompts::{AiPrompt, Prompt},
+    prompts::{AiPrompt, Prompt, PromptOverrides, build_system_prompt},
 };
 use groqai::{ChatMessage, GroqClient, MessageContent, Role};
-use regex::Regex;
 use sqlx::PgPool;
+use std::time::Duration;
 use teloxide::{
     prelude::*,
-    types::{ChatAction, ThreadId},
+    types::{ChatAction, InputFile, ThreadId},
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
+
+// Leading URLs read from one /search; more are treated as part of the question.
+const MAX_SEARCH_URLS: usize = 3;
+
+// Opens the model's reply when the pages don't hold the answer (see search_not_found.md).
+const NOT_FOUND_MARKER: &str = "[[NOT_FOUND]]";
 
 pub async fn search(
     bot: Bot,
     msg: Message,
     text: String,
-    scrapedo_token: String,
     pool: PgPool,
     groq: GroqClient,
-    models: Models,
+    app_config: AppConfig,
 ) -> Result<(), teloxide::RequestError> {
+    let scrapedo_token = &app_config.scrapedo_token;
+    let models = &app_config.models;
+    let search_cfg = &app_config.search;
+
     let chat_id = msg.chat.id;
     let thread_id: Option<ThreadId> = msg.thread_id;
 
-    // Keep Telegram "typing" action alive during long processing.
-    let mut keep =
-        ChatActionKeepAlive::spawn(bot.clone(), chat_id, thread_id, ChatAction::Typing, 4);
+    // Per-stage timings, logged when the command returns.
+    let mut stages = StageTimer::start("search");
+    stages.enter(PipelineStage::Validate);
+
+    // Keep Telegram "typing" action alive during long processing, unless a placeholder
+    // message stands in for it (edited commands get none, see send_placeholder).
+    let mut keep = if app_config.answer.placeholder && msg.edit_date().is_none() {
+        ChatActionKeepAlive::idle()
+    } else {
+        ChatActionKeepAlive::spawn(bot.clone(), chat_id, thread_id, ChatAction::Typing, 4)
+    };
 
     if text.trim().is_empty() {
         keep.shutdown().await;
@@ -44,31 +67,93 @@ pub async fn search(
             "I can't reply to an empty message. Use /search <url> <query>.",
             false,
             false,
+            None,
         )
         .await?;
         return Ok(());
     }
 
+    // `--lang <code>` picks the answer language instead of the user's Telegram language.
+    let (text, lang_override) = match take_lang_override(&text) {
+        Ok(v) => v,
+        Err(err_msg) => {
+            keep.shutdown().await;
+            send_reply_or_plain(&bot, &msg, err_msg, false, false, None).await?;
+            return Ok(());
+        }
+    };
+
+    // `--geo=<code>` fetches the pages from that country or region (scrape.do only).
+    let (text, geo) = match take_geo_option(&text) {
+        Ok(v) => v,
+        Err(err_msg) => {
+            keep.shutdown().await;
+            send_reply_or_plain(&bot, &msg, err_msg, false, false, None).await?;
+            return Ok(());
+        }
+    };
+
     // Prompt helper to access predefined system prompts.
     let prompts = AiPrompt::new();
 
     // Validate and extract user information.
-    let (user_id, user_lang, msg_chat_id) = match extract_user_info(&msg) {
+    let (user_id, detected_lang, msg_chat_id) = match extract_user_info(&msg, &app_config.identity)
+    {
         Ok(v) => v,
         Err(err_msg) => {
             // User-facing error, stop typing indicator, return
             keep.shutdown().await;
-            send_reply_or_plain(&bot, &msg, err_msg, false, false).await?;
+            send_reply_or_plain(&bot, &msg, err_msg, false, false, None).await?;
             return Ok(());
         }
     };
+    // The override only shapes this answer; the stored user language stays the detected one.
+    let user_lang = lang_override.unwrap_or_else(|| detected_lang.clone());
+    info!("Search answer language: {user_lang}");
+
+    // Ensure the provided text starts with at least one valid URL.
+    let urls = leading_urls(&text, MAX_SEARCH_URLS);
+    if urls.is_empty() {
+        error!("Search failed: Not URL to search");
+        keep.shutdown().await;
+        send_reply_or_plain(
+            &bot,
+            &msg,
+            "Use a valid URL (http:// or https://).",
+            false,
+            false,
+            None,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    // Stands in for the answer where "typing" isn't shown; the first reply below replaces it.
+    // Sent only once the request is valid, so input errors don't leave it behind.
+    if app_config.answer.placeholder {
+        send_placeholder(&bot, &msg).await;
+    }
+
+    // Detects a /reset that runs while this command is still working.
+    let reset_guard = ResetGuard::start(user_id, msg_chat_id);
 
     // Retrieve recent messages for context.
+    stages.enter(PipelineStage::FetchHistory);
+
+    // An edited command replaces its earlier answer, so the turn saved by that run goes
+    // too and doesn't come back as context for the re-run.
+    if msg.edit_date().is_some() {
+        match forget_edited_turn(&pool, user_id, msg_chat_id, i64::from(msg.id.0)).await {
+            Ok(0) => {}
+            Ok(n) => info!("Dropped {n} history rows saved before the command was edited"),
+            Err(e) => error!("Dropping the edited command's turn failed: {e}"),
+        }
+    }
     let history_limit: i32 = 30;
     let mut messages: Vec<MessageRow> = match sqlx::query_as!(
         MessageRow,
-        "SELECT content, ia_response FROM get_recent_messages($1, $2, $3, $4)",
-        user_lang,
+        "SELECT content, ia_response, age_secs FROM get_recent_messages($1, $2, $3, $4)",
+        detected_lang,
         user_id,
         msg_chat_id,
         history_limit,
@@ -80,84 +165,132 @@ pub async fn search(
         Err(e) => {
             error!("Query failed: {e}.");
             keep.shutdown().await;
-            send_reply_or_plain(&bot, &msg, "Database error", false, false).await?;
-            return Ok(());
-        }
-    };
-
-    // Ensure the provided text contains a valid URL.
-    let url_str = match text.split_whitespace().next() {
-        Some(candidate) => {
-            if !(candidate.starts_with("http://") || candidate.starts_with("https://")) {
-                error!("Search failed: Not URL to search");
-                keep.shutdown().await;
-                send_reply_or_plain(
-                    &bot,
-                    &msg,
-                    "Use a valid URL (http:// or https://).",
-                    false,
-                    false,
-                )
-                .await?;
-                return Ok(());
-            }
-
-            // Encode ampersands to keep query safe.
-            candidate.replace('&', "%26")
-        }
-        None => {
-            error!("Search failed: Not URL to search");
-            keep.shutdown().await;
             send_reply_or_plain(
                 &bot,
                 &msg,
-                "Use a valid URL (http:// or https://).",
+                "Database error",
                 false,
                 false,
+                app_config.answer.error_delete_after(),
             )
             .await?;
             return Ok(());
         }
     };
 
-    // Retrieve the simplified body of the web resource.
-    info!("Fetching simplified body");
-    let web_resource: String = match fetch_simplified_body(&format!(
-        "http://api.scrape.do/?token={scrapedo_token}&url={url_str}"
-    ))
-    .await
-    {
-        Ok(res) => {
-            let re = Regex::new(r"\{[^{}]*\}").unwrap();
-
-            if re.find(&res).is_some() && res.contains(r#""StatusCode":400"#) {
-                match fetch_simplified_body(&url_str).await {
-                    Ok(res) => res,
-                    Err(e) => {
-                        let err_text = e.clone();
-                        error!("Search failed: {}", err_text);
-                        keep.shutdown().await;
-                        send_reply_or_plain(&bot, &msg, "Search error.", false, false).await?;
-                        return Ok(());
-                    }
-                }
-            } else {
-                res
-            }
-        }
-        Err(e) => {
-            let err_text = e.clone();
-            error!("Search failed: {}", err_text);
-            keep.shutdown().await;
-            send_reply_or_plain(&bot, &msg, "Search error.", false, false).await?;
-            return Ok(());
+    // Retrieve the simplified body of every web resource; failed URLs are only noted.
+    stages.enter(PipelineStage::Scrape);
+    info!("Fetching simplified body of {} URLs", urls.len());
+    let token = scrapedo_token.clone();
+    let direct_domains = app_config.scrape_direct_domains.clone();
+    let scraped = scrape_pages(&urls, move |url| {
+        let token = token.clone();
+        let direct_domains = direct_domains.clone();
+        async move {
+            scrape_page(&token, &url, geo, &direct_domains)
+                .await
+                .map(|(fetched, _)| fetched.page)
         }
-    };
+    })
+    .await;
+
+    if scraped.pages.is_empty() {
+        error!("Search failed: no URL could be fetched");
+        keep.shutdown().await;
+        let reply = match scraped.failed.as_slice() {
+            [(_, e)] => format!("Search error: {}", e.user_message()),
+            failed => failed
+                .iter()
+                .map(|(url, e)| format!("Couldn't fetch {url}: {}", e.user_message()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+        send_reply_or_plain(
+            &bot,
+            &msg,
+            reply,
+            false,
+            false,
+            app_config.answer.error_delete_after(),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    // The preview uses the first page that loaded.
+    let metadata: PageMetadata = scraped.pages[0].1.metadata.clone();
+    let web_resource = combine_pages(&scraped.pages);
 
     // Build a single conversation array and use only the main model.
+    stages.enter(PipelineStage::ModelCall);
     let main_model = &models.thinking;
     let sec_model = &models.preprocessing;
-    let system_prompt = prompts.get(Prompt::ThinkAndFormat);
+    let mut system_prompt = build_system_prompt(
+        &app_config
+            .prompt
+            .answer_base(prompts.get(Prompt::ThinkAndFormat), true),
+        &user_lang,
+        &PromptOverrides::from_config(&app_config.answer),
+    );
+    system_prompt.push_str("\n\n");
+    system_prompt.push_str(&prompts.get(Prompt::SearchNotFound));
+    let text_timeout = Duration::from_secs(models.text_timeout_secs);
+
+    // Map-reduce: pages larger than one chunk are summarized per chunk with the
+    // preprocessing model, and only the combined summaries reach the main model.
+    let mut chunks_read = None;
+    let web_resource =
+        if search_cfg.chunk_chars > 0 && web_resource.chars().count() > search_cfg.chunk_chars {
+            let mut chunks = split_into_chunks(&web_resource, search_cfg.chunk_chars);
+            if chunks.len() > search_cfg.max_chunks {
+                warn!(
+                    "Web resource split into {} chunks, keeping the first {}",
+                    chunks.len(),
+                    search_cfg.max_chunks
+                );
+                chunks_read = Some((search_cfg.max_chunks, chunks.len()));
+                chunks.truncate(search_cfg.max_chunks);
+            }
+
+            info!("Summarizing web resource in {} chunks", chunks.len());
+            match summarize_chunks(
+                &groq,
+                &chunks,
+                &text,
+                sec_model,
+                prompts.get(Prompt::ChunkSummary),
+                text_timeout,
+            )
+            .await
+            {
+                Ok(summaries) => format!(
+                    "WebResource:\nSummaries of the page, split into {} parts:\n\n{}",
+                    chunks.len(),
+                    summaries.join("\n\n")
+                ),
+                Err(e) => {
+                    error!("Search failed: {e}");
+                    keep.shutdown().await;
+                    let reply = if is_rate_limit_error(&e) {
+                        model_error_message(&e)
+                    } else {
+                        "Search error.".to_string()
+                    };
+                    send_reply_or_plain(
+                        &bot,
+                        &msg,
+                        reply,
+                        false,
+                        false,
+                        app_config.answer.error_delete_after(),
+                    )
+                    .await?;
+                    return Ok(());
+                }
+            }
+        } else {
+            web_resource
+        };
 
     // Build conversation: system, historical turns, then the current user message.
     let mut convo: Vec<ChatMessage> = Vec::new();
@@ -165,9 +298,15 @@ pub async fn search(
 
     // Append historical turns (if any). For each saved row: user content then assistant response.
     messages.reverse();
+    // With HISTORY_TIMESTAMPS each turn says how long ago it was, for recency.
+    let timestamps = app_config.history.timestamps;
     for row in &messages {
         if let Some(ref user_content) = row.content {
-            convo.push(ChatMessage::new_text(Role::User, user_content.clone()));
+            let age = row.age_secs.filter(|_| timestamps);
+            convo.push(ChatMessage::new_text(
+                Role::User,
+                with_age(user_content, age),
+            ));
         }
         if let Some(ref assistant_content) = row.ia_response {
             convo.push(ChatMessage::new_text(
@@ -178,27 +317,34 @@ pub async fn search(
     }
 
     // Add the user prompt (HTML will be passed separately below).
-    let current_user_msg = format!("Main lang is \"{user_lang}\":\n\nUser prompt: {}", text);
+    let current_user_msg = format!("User prompt: {}", text);
     convo.push(ChatMessage::new_text(Role::User, current_user_msg));
 
     // Pass the fetched HTML/body as a separate user message to improve tokenization/context handling.
-    convo.push(ChatMessage::new_text(
-        Role::User,
-        format!("WebResource:\n{}", &web_resource),
-    ));
-
-    let resp = match groq
-        .chat(main_model)
-        .messages(convo)
-        .max_completion_tokens(3000)
-        .temperature(0.0)
-        .send()
-        .await
+    convo.push(ChatMessage::new_text(Role::User, web_resource.clone()));
+
+    let resp = match with_model_timeout(
+        text_timeout,
+        groq.chat(main_model)
+            .messages(convo)
+            .max_completion_tokens(3000)
+            .temperature(clamp_temperature(Provider::Groq, models.temperature))
+            .send(),
+    )
+    .await
     {
         Ok(r) => r,
         Err(e) => {
             keep.shutdown().await;
-            send_reply_or_plain(&bot, &msg, format!("Error: {e}."), false, false).await?;
+            send_reply_or_plain(
+                &bot,
+                &msg,
+                model_error_message(&e),
+                false,
+                false,
+                app_config.answer.error_delete_after(),
+            )
+            .await?;
             return Ok(());
         }
     };
@@ -209,11 +355,57 @@ pub async fn search(
         String::new()
     };
 
-    let final_answer = escape_telegram_code_entities(&raw_answer);
+    // "Not in the page" is a valid result: shown as such rather than like a failure.
+    let (raw_answer, not_found) = match strip_not_found_marker(&raw_answer) {
+        Some(rest) => {
+            info!("The fetched pages don't contain the answer");
+            (rest, true)
+        }
+        None => (raw_answer, false),
+    };
+
+    stages.enter(PipelineStage::Format);
+    let profile = app_config.answer.profile("search");
+    let answer_pipeline = AnswerPipeline::for_profile(&app_config.answer, profile);
+    let mut final_answer = answer_pipeline.apply(&raw_answer);
+    if not_found {
+        let label = if scraped.pages.len() > 1 {
+            "The pages don't contain that information."
+        } else {
+            "The page doesn't contain that information."
+        };
+        final_answer = format!("<b>{label}</b>\n\n{final_answer}")
+            .trim_end()
+            .to_string();
+    }
+    // The language note is only shown, not saved into the history.
+    let mut reply = if search_cfg.show_lang {
+        format!("<i>Language: {user_lang}</i>\n\n{final_answer}")
+    } else {
+        final_answer.clone()
+    };
+    if profile.show_sources() {
+        reply.push_str(&sources_line(
+            scraped.pages.iter().map(|(url, _)| url.as_str()),
+        ));
+    }
+    for (url, e) in &scraped.failed {
+        reply.push_str(&format!(
+            "\n\n<i>Couldn't fetch: {} ({})</i>",
+            html_escape::encode_text(url),
+            html_escape::encode_text(&e.user_message())
+        ));
+    }
+    if let Some((read, total)) = chunks_read {
+        reply.push_str(&format!(
+            "\n\n<i>The page was too long: only the first {read} of {total} parts were read.</i>"
+        ));
+    }
 
     keep.shutdown().await;
 
-    let send_req = send_reply_or_plain(&bot, &msg, final_answer.clone(), false, true);
+    stages.enter(PipelineStage::Send);
+    let send_req = send_answer(&bot, &msg, reply, &app_config.answer);
 
     if let Err(e) = send_req.await {
         let err_text = e.to_string();
@@ -222,21 +414,32 @@ pub async fn search(
             error!("Telegram parse error: {}.", err_text);
 
             // Ask preprocessing model to try to apply HTML/formatting to the raw model output
-            let fmt_res = match groq
-                .chat(sec_model)
-                .messages(vec![
-                    ChatMessage::new_text(Role::System, prompts.get(Prompt::Html)),
-                    ChatMessage::new_text(Role::User, raw_answer.clone()),
-                ])
-                .max_completion_tokens(3000)
-                .temperature(0.0)
-                .send()
-                .await
+            stages.enter(PipelineStage::ModelCall);
+            let fmt_res = match with_model_timeout(
+                text_timeout,
+                groq.chat(sec_model)
+                    .messages(vec![
+                        ChatMessage::new_text(Role::System, prompts.get(Prompt::Html)),
+                        ChatMessage::new_text(Role::User, raw_answer.clone()),
+                    ])
+                    .max_completion_tokens(3000)
+                    .temperature(0.0)
+                    .send(),
+            )
+            .await
             {
                 Ok(r) => r,
                 Err(e) => {
                     keep.shutdown().await;
-                    send_reply_or_plain(&bot, &msg, format!("Error: {e}."), false, false).await?;
+                    send_reply_or_plain(
+                        &bot,
+                        &msg,
+                        model_error_message(&e),
+                        false,
+                        false,
+                        app_config.answer.error_delete_after(),
+                    )
+                    .await?;
                     return Ok(());
                 }
             };
@@ -247,9 +450,12 @@ pub async fn search(
                 String::new()
             };
 
-            let reformated_answer = escape_telegram_code_entities(&fmt_text);
+            stages.enter(PipelineStage::Format);
+            let reformated_answer = answer_pipeline.apply(&fmt_text);
 
-            let fmt_req = send_reply_or_plain(&bot, &msg, &reformated_answer, false, true);
+            // Same path as the first attempt: file threshold, placeholder and edit handling.
+            stages.enter(PipelineStage::Send);
+            let fmt_req = send_answer(&bot, &msg, reformated_answer, &app_config.answer);
 
             if let Err(e) = fmt_req.await {
                 error!("Telegram send failed: {e} — no DB transaction to roll back.");
@@ -266,15 +472,37 @@ pub async fn search(
         return Ok(());
     }
 
+    if search_cfg.og_preview {
+        send_og_preview(&bot, &msg, &metadata).await;
+    }
+
+    // The history was reset meanwhile: don't bring this turn back into it.
+    if reset_guard.reset_since_start() {
+        info!("History reset during the command, not saving this turn");
+        return Ok(());
+    }
+
+    // Cut on char boundaries so oversized rows never store broken UTF-8.
+    stages.enter(PipelineStage::Persist);
+    let max_chars = app_config.history.max_chars;
+    let stored_content = truncate_for_storage(
+        &format!("{text}\n\nWeb Resource:\n\n{web_resource}"),
+        max_chars,
+        "content",
+    );
+    let stored_answer = truncate_for_storage(&final_answer, max_chars, "ia_response");
+
     if let Err(e) = sqlx::query!(
         r#"
-        INSERT INTO messages (user_telegram_id, chat_telegram_id, content, ia_response)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO messages
+          (user_telegram_id, chat_telegram_id, content, ia_response, message_telegram_id)
+        VALUES ($1, $2, $3, $4, $5)
         "#,
         user_id,
         msg_chat_id,
-        format!("{text}\n\nWeb Resource:\n\n{web_resource}"),
-        final_answer,
+        stored_content,
+        stored_answer,
+        i64::from(msg.id.0),
     )
     .execute(&pool)
     .await
@@ -286,10 +514,295 @@ pub async fn search(
             "Database error (couldn't save message).",
             false,
             false,
+            app_config.answer.error_delete_after(),
         )
         .await?;
         return Ok(());
     }
 
+    // Retention policy: keep only the newest rows for this user/chat.
+    if app_config.history.retention > 0 {
+        match prune_history(&pool, user_id, msg_chat_id, app_config.history.retention).await {
+            Ok(0) => {}
+            Ok(pruned) => info!("Pruned {pruned} old history rows"),
+            Err(e) => error!("History pruning failed: {e}"),
+        }
+    }
+
     Ok(())
 }
+
+// Send the page's og:image as a photo captioned with its og:title; failures only log.
+async fn send_og_preview(bot: &Bot, msg: &Message, metadata: &PageMetadata) {
+    let Some(image) = metadata.image.as_deref() else {
+        return;
+    };
+    // Relative image paths can't be fetched by Telegram.
+    let Ok(image_url) = reqwest::Url::parse(image) else {
+        return;
+    };
+
+    let mut req = bot.send_photo(msg.chat.id, InputFile::url(image_url));
+    if let Some(title) = metadata.title.as_deref() {
+        req = req.caption(title);
+    }
+    if let Some(tid) = msg.thread_id {
+        req = req.message_thread_id(tid);
+    }
+
+    if let Err(e) = req.await {
+        warn!("OpenGraph preview not sent: {e}");
+    }
+}
+
+// The http(s) URLs at the start of the command text, at most `max`.
+fn leading_urls(text: &str, max: usize) -> Vec<String> {
+    text.split_whitespace()
+        .take_while(|w| w.starts_with("http://") || w.starts_with("https://"))
+        .take(max)
+        .map(str::to_string)
+        .collect()
+}
+
+// Model context for the fetched pages: metadata and body of each, labelled by URL when
+// there are several.
+fn combine_pages(pages: &[(String, SimplifiedPage)]) -> String {
+    if let [(_, page)] = pages {
+        return format!("{}WebResource:\n{}", page.metadata.to_context(), page.body);
+    }
+    pages
+        .iter()
+        .map(|(url, page)| {
+            format!(
+                "{}WebResource ({url}):\n{}",
+                page.metadata.to_context(),
+                page.body
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+// Links to the pages the answer was built from, labelled by host.
+fn sources_line<'a>(urls: impl Iterator<Item = &'a str>) -> String {
+    let links: Vec<String> = urls
+        .map(|url| {
+            let label = reqwest::Url::parse(url)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string))
+                .unwrap_or_else(|| url.to_string());
+            format!(
+                "<a href=\"{}\">{}</a>",
+                html_escape::encode_double_quoted_attribute(url),
+                html_escape::encode_text(&label)
+            )
+        })
+        .collect();
+    format!("\n\n<i>Sources:</i> {}", links.join(", "))
+}
+
+// The answer without the not-found marker, or None if the model didn't set it.
+fn strip_not_found_marker(answer: &str) -> Option<String> {
+    answer
+        .contains(NOT_FOUND_MARKER)
+        .then(|| answer.replacen(NOT_FOUND_MARKER, "", 1).trim().to_string())
+}
+
+// Byte ranges of the words in `text`, so options can be cut out without touching the
+// spacing and line breaks of the rest.
+fn word_ranges(text: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    text.split_whitespace().map(move |word| {
+        let start = word.as_ptr() as usize - text.as_ptr() as usize;
+        (start, start + word.len())
+    })
+}
+
+// `text` without the ranges in `cut` (ascending), each taken with the whitespace after it,
+// or before it at the end of the text.
+fn cut_ranges(text: &str, cut: &[(usize, usize)]) -> String {
+    let mut kept = String::with_capacity(text.len());
+    let mut pos = 0;
+    for &(start, end) in cut {
+        let end = text.len() - text[end..].trim_start().len();
+        let start = if end == text.len() {
+            text[..start].trim_end().len().max(pos)
+        } else {
+            start
+        };
+        kept.push_str(&text[pos..start]);
+        pos = end;
+    }
+    kept.push_str(&text[pos..]);
+    kept
+}
+
+// Remove a `--lang <code>` option from the command text and return the code.
+// Codes are letters with an optional region, e.g. "es" or "pt-BR".
+fn take_lang_override(text: &str) -> Result<(String, Option<String>), &'static str> {
+    const USAGE: &str = "Use --lang <code> with a language code such as es or pt-BR.";
+
+    let mut words = word_ranges(text);
+    let mut cut = Vec::new();
+    let mut lang = None;
+
+    while let Some((start, end)) = words.next() {
+        if &text[start..end] != "--lang" {
+            continue;
+        }
+        let (code_start, code_end) = words.next().ok_or(USAGE)?;
+        let code = &text[code_start..code_end];
+        let valid = code.split('-').all(|part| {
+            (2..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+        }) && code.chars().next().is_some_and(|c| c.is_ascii_alphabetic());
+        if !valid {
+            return Err(USAGE);
+        }
+        lang = Some(code.to_string());
+        cut.push((start, code_end));
+    }
+
+    Ok((cut_ranges(text, &cut), lang))
+}
+
+// Remove a `--geo=<code>` (or `--geo <code>`) option and return the location, which must
+// be a country or region scrape.do supports.
+fn take_geo_option(text: &str) -> Result<(String, Option<ScrapeGeo>), &'static str> {
+    const USAGE: &str =
+        "Use --geo=<code> with a country such as us or es, or a region such as europe.";
+
+    let mut words = word_ranges(text);
+    let mut cut = Vec::new();
+    let mut geo = None;
+
+    while let Some((start, end)) = words.next() {
+        let (code, end) = match text[start..end].strip_prefix("--geo") {
+            Some("") => {
+                let (code_start, code_end) = words.next().ok_or(USAGE)?;
+                (&text[code_start..code_end], code_end)
+            }
+            Some(rest) => (rest.strip_prefix('=').ok_or(USAGE)?, end),
+            None => continue,
+        };
+        geo = Some(ScrapeGeo::parse(code).ok_or(USAGE)?);
+        cut.push((start, end));
+    }
+
+    Ok((cut_ranges(text, &cut), geo))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        NOT_FOUND_MARKER, leading_urls, sources_line, strip_not_found_marker, take_geo_option,
+        take_lang_override,
+    };
+    use crate::handlers::utils::ScrapeGeo;
+    use crate::prompts::{AiPrompt, Prompt};
+
+    #[test]
+    fn extracts_lang_option_anywhere() {
+        assert_eq!(
+            take_lang_override("--lang pt-BR https://e.io what is this").unwrap(),
+            (
+                "https://e.io what is this".to_string(),
+                Some("pt-BR".to_string())
+            )
+        );
+        assert_eq!(
+            take_lang_override("https://e.io resume --lang es").unwrap(),
+            ("https://e.io resume".to_string(), Some("es".to_string()))
+        );
+    }
+
+    #[test]
+    fn text_without_option_is_unchanged() {
+        assert_eq!(
+            take_lang_override("https://e.io  question\n  - one\n  - two").unwrap(),
+            ("https://e.io  question\n  - one\n  - two".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn removing_the_option_keeps_line_breaks() {
+        assert_eq!(
+            take_lang_override("https://e.io\n--lang es\ncompare:\n  - a\n  - b").unwrap(),
+            (
+                "https://e.io\ncompare:\n  - a\n  - b".to_string(),
+                Some("es".to_string())
+            )
+        );
+    }
+
+    #[test]
+    fn reads_only_leading_urls() {
+        assert_eq!(
+            leading_urls("https://a.io http://b.io compare https://c.io", 3),
+            vec!["https://a.io".to_string(), "http://b.io".to_string()]
+        );
+        assert_eq!(leading_urls("https://a.io https://b.io x", 1).len(), 1);
+        assert!(leading_urls("what is https://a.io", 3).is_empty());
+    }
+
+    #[test]
+    fn lists_sources_by_host() {
+        assert_eq!(
+            sources_line(["https://a.io/x?p=1&q=2", "http://b.io"].into_iter()),
+            "\n\n<i>Sources:</i> <a href=\"https://a.io/x?p=1&amp;q=2\">a.io</a>, \
+             <a href=\"http://b.io\">b.io</a>"
+        );
+    }
+
+    #[test]
+    fn detects_the_not_found_marker() {
+        assert!(
+            AiPrompt::new()
+                .get(Prompt::SearchNotFound)
+                .contains(NOT_FOUND_MARKER)
+        );
+
+        assert_eq!(
+            strip_not_found_marker("[[NOT_FOUND]]\nThe page is about pricing plans."),
+            Some("The page is about pricing plans.".to_string())
+        );
+        assert_eq!(strip_not_found_marker("[[NOT_FOUND]]"), Some(String::new()));
+        assert_eq!(strip_not_found_marker("The plan costs $5."), None);
+    }
+
+    #[test]
+    fn extracts_geo_option_before_the_urls() {
+        assert_eq!(
+            take_geo_option("--geo=us https://e.io price").unwrap(),
+            (
+                "https://e.io price".to_string(),
+                Some(ScrapeGeo::Country("us"))
+            )
+        );
+        assert_eq!(
+            take_geo_option("https://e.io --geo europe price").unwrap(),
+            (
+                "https://e.io price".to_string(),
+                Some(ScrapeGeo::Region("europe"))
+            )
+        );
+        assert_eq!(
+            take_geo_option("https://e.io  price\n  in euros").unwrap(),
+            ("https://e.io  price\n  in euros".to_string(), None)
+        );
+        assert_eq!(
+            take_geo_option("https://e.io --geo=es\nprice\n  in euros").unwrap(),
+            (
+                "https://e.io price\n  in euros".to_string(),
+                Some(ScrapeGeo::Country("es"))
+            )
+        );
+        assert!(take_geo_option("--geo=xx https://e.io").is_err());
+        assert!(take_geo_option("--geo").is_err());
+        assert!(take_geo_option("--geography https://e.io").is_err());
+    }
+
+    #[test]
+    fn rejects_missing_or_invalid_code() {
+        assert!(take_lang_override("https://e.io --lang").is_err());
+        assert!(take_lang_override("https://e.io --lang <b>").is_err());
+    }
+}
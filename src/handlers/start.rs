@@ -23,7 +23,7 @@ pub async fn start(bot: Bot, msg: Message) -> Result<(), teloxide::RequestError>
 
     keep.shutdown().await;
 
-    if let Err(e) = send_reply_or_plain(&bot, &msg, message, false, false).await {
+    if let Err(e) = send_reply_or_plain(&bot, &msg, message, false, false, None).await {
         error!("Telegram send failed: {e}");
         return Err(e);
     }
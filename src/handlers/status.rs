@@ -0,0 +1,103 @@
+// Handler for the /status command: in-chat health report for operators.
+
+use crate::{
+    config::AppConfig,
+    handlers::utils::{llm::last_model_call, send_reply_or_plain},
+};
+use once_cell::sync::OnceCell;
+use sqlx::PgPool;
+use std::time::{Duration, Instant};
+use teloxide::prelude::*;
+use tracing::warn;
+
+static STARTED_AT: OnceCell<Instant> = OnceCell::new();
+
+// Record the process start so /status can report uptime.
+pub fn mark_started() {
+    let _ = STARTED_AT.set(Instant::now());
+}
+
+// Compact duration such as "2d 3h 4m 5s", skipping leading zero units.
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let parts = [
+        (secs / 86_400, "d"),
+        (secs % 86_400 / 3600, "h"),
+        (secs % 3600 / 60, "m"),
+        (secs % 60, "s"),
+    ];
+    let out: Vec<String> = parts
+        .iter()
+        .skip_while(|(n, unit)| *n == 0 && *unit != "s")
+        .map(|(n, unit)| format!("{n}{unit}"))
+        .collect();
+    out.join(" ")
+}
+
+pub async fn status(
+    bot: Bot,
+    msg: Message,
+    pool: PgPool,
+    app_config: AppConfig,
+) -> Result<(), teloxide::RequestError> {
+    let is_admin = msg
+        .from
+        .as_ref()
+        .is_some_and(|u| app_config.is_admin(u.id.0));
+    if !is_admin {
+        send_reply_or_plain(&bot, &msg, "I'm up.", false, false, None).await?;
+        return Ok(());
+    }
+
+    let uptime = STARTED_AT
+        .get()
+        .map(|t| format_duration(t.elapsed()))
+        .unwrap_or_else(|| "unknown".to_string());
+    let mode = if app_config.hosting {
+        "webhook"
+    } else {
+        "polling"
+    };
+
+    let db_started = Instant::now();
+    let database = match sqlx::query("SELECT 1").execute(&pool).await {
+        Ok(_) => format!("ok ({} ms)", db_started.elapsed().as_millis()),
+        Err(e) => {
+            warn!("Status database check failed: {e}");
+            "unreachable".to_string()
+        }
+    };
+
+    let groq = match last_model_call() {
+        Some((at, true)) => format!("ok, {} ago", format_duration(at.elapsed())),
+        Some((at, false)) => format!("failed, {} ago", format_duration(at.elapsed())),
+        None => "no calls yet".to_string(),
+    };
+
+    let report = format!(
+        "<b>Status</b>\n\
+         Uptime: {uptime}\n\
+         Mode: {mode}\n\
+         Database: {database}\n\
+         Groq (last call): {groq}"
+    );
+    send_reply_or_plain(&bot, &msg, report, false, true, None).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_duration;
+    use std::time::Duration;
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(Duration::from_secs(0)), "0s");
+        assert_eq!(format_duration(Duration::from_secs(65)), "1m 5s");
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 86_400 + 3 * 3600 + 4 * 60 + 5)),
+            "2d 3h 4m 5s"
+        );
+        assert_eq!(format_duration(Duration::from_secs(3600)), "1h 0m 0s");
+    }
+}
@@ -2,4 +2,6 @@
 pub struct MessageRow {
     pub content: Option<String>,
     pub ia_response: Option<String>,
+    // Seconds since the turn was saved.
+    pub age_secs: Option<i64>,
 }
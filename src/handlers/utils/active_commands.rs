@@ -0,0 +1,146 @@
+// Commands still running, so a shutdown can tell their users to send them again instead
+// of leaving a "typing" indicator that silently stops, and cancel them so no late answer
+// follows the notice.
+
+use super::send_reply_or_plain;
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+use teloxide::prelude::*;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+const RESTART_NOTICE: &str =
+    "I'm restarting, so your request was interrupted. Please send it again in a minute.";
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+// A running command's message and its cancel signal.
+type Tracked = (Message, Arc<Notify>);
+
+// Registration id -> the tracked command.
+static ACTIVE: Lazy<Mutex<HashMap<u64, Tracked>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Registers a command while alive; dropping it (the command ended) unregisters it.
+pub struct ActiveCommand {
+    id: u64,
+    cancel: Arc<Notify>,
+}
+
+impl ActiveCommand {
+    pub fn track(msg: &Message) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let cancel = Arc::new(Notify::new());
+        if let Ok(mut active) = ACTIVE.lock() {
+            active.insert(id, (msg.clone(), cancel.clone()));
+        }
+        Self { id, cancel }
+    }
+
+    // Resolves once a shutdown cancelled the command; the caller drops its work then.
+    pub async fn cancelled(&self) {
+        self.cancel.notified().await;
+    }
+}
+
+impl Drop for ActiveCommand {
+    fn drop(&mut self) {
+        if let Ok(mut active) = ACTIVE.lock() {
+            active.remove(&self.id);
+        }
+    }
+}
+
+// Messages of the commands running right now.
+pub fn active_commands() -> Vec<Message> {
+    ACTIVE
+        .lock()
+        .map(|active| active.values().map(|(msg, _)| msg.clone()).collect())
+        .unwrap_or_default()
+}
+
+// Cancel every running command and reply to it that the bot is restarting, giving up
+// after `limit` so a slow Telegram doesn't hold the shutdown.
+pub async fn notify_active_commands(bot: &Bot, limit: Duration) {
+    let pending: Vec<Tracked> = ACTIVE
+        .lock()
+        .map(|active| active.values().cloned().collect())
+        .unwrap_or_default();
+    if pending.is_empty() {
+        return;
+    }
+    info!(
+        "Telling {} running commands about the restart",
+        pending.len()
+    );
+
+    let mut notices = tokio::task::JoinSet::new();
+    for (msg, cancel) in pending {
+        // Cancelled before the notice, so the user can't get both it and a late answer.
+        cancel.notify_one();
+        let bot = bot.clone();
+        notices.spawn(async move {
+            if let Err(e) = send_reply_or_plain(&bot, &msg, RESTART_NOTICE, true, false, None).await
+            {
+                warn!("Restart notice to chat {} failed: {e}", msg.chat.id);
+            }
+        });
+    }
+    if tokio::time::timeout(limit, notices.join_all())
+        .await
+        .is_err()
+    {
+        warn!("Restart notices took longer than {limit:?}, not waiting for the rest");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ActiveCommand, active_commands, notify_active_commands};
+    use std::time::Duration;
+    use teloxide::{Bot, types::Message};
+
+    fn message(id: i32) -> Message {
+        serde_json::from_value(serde_json::json!({
+            "message_id": id,
+            "date": 0,
+            "chat": { "id": 770000123, "type": "private", "first_name": "A" },
+            "from": { "id": 1, "is_bot": false, "first_name": "A" },
+            "text": "/ask hi"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn commands_are_tracked_while_running() {
+        let is_active = |id: i32| active_commands().iter().any(|m| m.id.0 == id);
+
+        let first = ActiveCommand::track(&message(880001));
+        let second = ActiveCommand::track(&message(880002));
+        assert!(is_active(880001) && is_active(880002));
+
+        drop(first);
+        assert!(!is_active(880001) && is_active(880002));
+        drop(second);
+        assert!(!is_active(880002));
+    }
+
+    #[tokio::test]
+    async fn shutdown_cancels_the_notified_commands() {
+        let command = ActiveCommand::track(&message(880003));
+
+        // Unreachable API: the notice fails fast, the command is cancelled regardless.
+        let bot = Bot::new("tok").set_api_url("http://127.0.0.1:1/".parse().unwrap());
+        notify_active_commands(&bot, Duration::from_secs(5)).await;
+
+        tokio::time::timeout(Duration::from_secs(1), command.cancelled())
+            .await
+            .expect("the command was not cancelled");
+    }
+}
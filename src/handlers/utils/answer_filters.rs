@@ -0,0 +1,427 @@
+// Ordered post-processing applied to model answers before they are sent.
+
+use super::{
+    escape_telegram_code_entities, html_to_plain_text, truncate_chars, truncation_indicator,
+};
+use crate::config::{AnswerConfig, AnswerProfile};
+use html_escape::encode_text;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use teloxide::types::ParseMode;
+use tracing::info;
+
+// A single fenced block: text before, language hint, code, text after.
+static FENCE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)^(.*?)```([A-Za-z0-9_+#.-]*)[^\n]*\n(.*?)\n?```(.*)$").unwrap());
+
+// One step of the answer pipeline.
+pub trait AnswerFilter: Send + Sync {
+    fn apply(&self, answer: String) -> String;
+}
+
+// Escape the answer for Telegram HTML while keeping <code> blocks and entities.
+pub struct EscapeForTelegram;
+
+impl AnswerFilter for EscapeForTelegram {
+    fn apply(&self, answer: String) -> String {
+        escape_telegram_code_entities(&answer)
+    }
+}
+
+// Replace every match of `pattern` (e.g. phone numbers) with `replacement`.
+pub struct Redact {
+    pub pattern: Regex,
+    pub replacement: String,
+}
+
+impl AnswerFilter for Redact {
+    fn apply(&self, answer: String) -> String {
+        self.pattern
+            .replace_all(&answer, self.replacement.as_str())
+            .into_owned()
+    }
+}
+
+// Drop every tag and send the text alone, escaped so it still travels as HTML.
+pub struct StripMarkup;
+
+impl AnswerFilter for StripMarkup {
+    fn apply(&self, answer: String) -> String {
+        encode_text(&html_to_plain_text(&answer)).into_owned()
+    }
+}
+
+// Answers that are mostly one fenced code block become <pre><code class="language-X">,
+// with the code escaped here so the later escaping step leaves it as is.
+pub struct FormatCodeOnlyAnswer;
+
+impl FormatCodeOnlyAnswer {
+    // Share of code in code + surrounding text (in chars) to count as "code only".
+    const MIN_CODE_SHARE: f64 = 0.8;
+}
+
+impl AnswerFilter for FormatCodeOnlyAnswer {
+    fn apply(&self, answer: String) -> String {
+        if answer.matches("```").count() != 2 {
+            return answer;
+        }
+        let Some(caps) = FENCE_RE.captures(&answer) else {
+            return answer;
+        };
+
+        // The fences themselves don't count: only the code against the prose around it.
+        let code = caps[3].trim_matches('\n');
+        let code_len = code.chars().count();
+        let total = code_len + caps[1].trim().chars().count() + caps[4].trim().chars().count();
+        if code_len == 0 || (code_len as f64) < total as f64 * Self::MIN_CODE_SHARE {
+            return answer;
+        }
+
+        let block = match &caps[2] {
+            "" => format!("<pre><code>{}</code></pre>", encode_text(code)),
+            lang => format!(
+                "<pre><code class=\"language-{}\">{}</code></pre>",
+                lang.to_lowercase(),
+                encode_text(code)
+            ),
+        };
+        [caps[1].trim(), block.as_str(), caps[4].trim()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// Opening or closing HTML tag, for closing the ones a trim leaves open.
+static TAG_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<(/?)([A-Za-z][A-Za-z0-9-]*)[^<>]*>").unwrap());
+
+// Cap the answer at `max_chars`, cutting at the last paragraph, line, sentence or word
+// break in its second half, and add `note` (already escaped for Telegram HTML). Runs on the
+// raw answer: a fence or tags the cut leaves open are closed so the escaping step still
+// sees balanced markup.
+pub struct TrimAnswer {
+    pub max_chars: usize,
+    pub note: String,
+}
+
+impl TrimAnswer {
+    // Byte index to cut `text` at, keeping at most `max_chars` chars.
+    fn boundary(text: &str, max_chars: usize) -> usize {
+        let window = truncate_chars(text, max_chars);
+        let min = window.len() / 2;
+        for sep in ["\n\n", "\n", ". ", " "] {
+            if let Some(idx) = window.rfind(sep)
+                && idx >= min
+            {
+                // Keep the sentence's period.
+                return if sep == ". " { idx + 1 } else { idx };
+            }
+        }
+        window.len()
+    }
+}
+
+impl AnswerFilter for TrimAnswer {
+    fn apply(&self, answer: String) -> String {
+        if self.max_chars == 0 || answer.chars().count() <= self.max_chars {
+            return answer;
+        }
+
+        let mut kept = answer[..Self::boundary(&answer, self.max_chars)]
+            .trim_end()
+            .to_string();
+        // A tag cut in half is dropped (a lone "<" in prose is not a tag).
+        if let Some(lt) = kept.rfind('<')
+            && !kept[lt..].contains('>')
+            && kept[lt + 1..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '/')
+        {
+            kept.truncate(lt);
+            kept.truncate(kept.trim_end().len());
+        }
+
+        if kept.matches("```").count() % 2 == 1 {
+            kept.push_str("\n```");
+        } else {
+            let mut open: Vec<String> = Vec::new();
+            for caps in TAG_RE.captures_iter(&kept) {
+                let name = caps[2].to_lowercase();
+                if name == "br" {
+                    continue;
+                }
+                if caps[1].is_empty() {
+                    open.push(name);
+                } else if let Some(pos) = open.iter().rposition(|n| *n == name) {
+                    open.truncate(pos);
+                }
+            }
+            for name in open.iter().rev() {
+                kept.push_str(&format!("</{name}>"));
+            }
+        }
+
+        info!(
+            "Trimmed answer from {} to {} chars",
+            answer.chars().count(),
+            kept.chars().count()
+        );
+        format!("{kept}\n\n{}", self.note)
+    }
+}
+
+// Append a fixed footer (may contain Telegram HTML) after a blank line.
+pub struct AppendFooter(pub String);
+
+impl AnswerFilter for AppendFooter {
+    fn apply(&self, answer: String) -> String {
+        format!("{answer}\n\n{}", self.0)
+    }
+}
+
+#[derive(Default)]
+pub struct AnswerPipeline {
+    filters: Vec<Box<dyn AnswerFilter>>,
+}
+
+impl AnswerPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, filter: impl AnswerFilter + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    // Built-in pipeline: redaction and trimming on the raw text, code-only formatting,
+    // escaping, then the footer (appended last so its HTML is not escaped).
+    pub fn from_config(cfg: &AnswerConfig) -> Self {
+        Self::for_profile(cfg, AnswerProfile::Conversational)
+    }
+
+    // The configured pipeline, with the length and markup limits of `profile` on top.
+    pub fn for_profile(cfg: &AnswerConfig, profile: AnswerProfile) -> Self {
+        let mut pipeline = Self::new();
+        if let Some(pattern) = &cfg.redact {
+            pipeline = pipeline.with(Redact {
+                pattern: pattern.clone(),
+                replacement: cfg.redact_replacement.clone(),
+            });
+        }
+        // The stricter of the two limits wins; 0 means unlimited.
+        let max_chars = match (cfg.max_chars, profile.max_chars()) {
+            (0, n) | (n, 0) => n,
+            (a, b) => a.min(b),
+        };
+        if max_chars > 0 {
+            // Every profile is sent as HTML; the later steps keep the note's entities.
+            pipeline = pipeline.with(TrimAnswer {
+                max_chars,
+                note: truncation_indicator(&cfg.truncation_label, Some(ParseMode::Html)),
+            });
+        }
+        if !profile.html() {
+            pipeline = pipeline.with(StripMarkup);
+        } else {
+            if cfg.code_blocks {
+                pipeline = pipeline.with(FormatCodeOnlyAnswer);
+            }
+            pipeline = pipeline.with(EscapeForTelegram);
+        }
+        if let Some(footer) = &cfg.footer {
+            pipeline = pipeline.with(AppendFooter(footer.clone()));
+        }
+        pipeline
+    }
+
+    pub fn apply(&self, answer: &str) -> String {
+        self.filters
+            .iter()
+            .fold(answer.to_string(), |acc, filter| filter.apply(acc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_run_in_order() {
+        let pipeline = AnswerPipeline::new()
+            .with(AppendFooter("one".into()))
+            .with(AppendFooter("two".into()));
+        assert_eq!(pipeline.apply("answer"), "answer\n\none\n\ntwo");
+    }
+
+    #[test]
+    fn config_pipeline_redacts_escapes_and_appends_footer() {
+        let cfg = AnswerConfig {
+            redact: Some(Regex::new(r"\+?\d{3}-\d{4}").unwrap()),
+            redact_replacement: "[redacted]".into(),
+            footer: Some("<i>Not financial advice.</i>".into()),
+            ..AnswerConfig::default()
+        };
+        let out = AnswerPipeline::from_config(&cfg).apply("Call 555-1234 & ask");
+        assert_eq!(
+            out,
+            "Call [redacted] &amp; ask\n\n<i>Not financial advice.</i>"
+        );
+    }
+
+    #[test]
+    fn code_only_answer_with_language_hint() {
+        let answer = "```rust\nfn main() {\n    let v: Vec<i32> = vec![];\n}\n```".to_string();
+        let out = AnswerPipeline::from_config(&AnswerConfig::default()).apply(&answer);
+        assert_eq!(
+            out,
+            "<pre><code class=\"language-rust\">fn main() {\n    let v: Vec&lt;i32&gt; = vec![];\n}</code></pre>"
+        );
+    }
+
+    #[test]
+    fn code_only_answer_without_language_hint() {
+        let answer = "Here:\n```\nSELECT a & b FROM t WHERE x < 1;\n```".to_string();
+        assert_eq!(
+            FormatCodeOnlyAnswer.apply(answer),
+            "Here:\n<pre><code>SELECT a &amp; b FROM t WHERE x &lt; 1;</code></pre>"
+        );
+    }
+
+    #[test]
+    fn prose_and_multiple_fences_are_left_alone() {
+        let prose = "This explains a lot of things in detail before a tiny example:\n```\nx\n```";
+        assert_eq!(FormatCodeOnlyAnswer.apply(prose.into()), prose);
+
+        let two = "```\nlet a = 1;\n```\n```\nlet b = 2;\n```";
+        assert_eq!(FormatCodeOnlyAnswer.apply(two.into()), two);
+
+        let disabled = AnswerConfig {
+            code_blocks: false,
+            ..AnswerConfig::default()
+        };
+        let fenced = "```\nlet a = 1;\n```";
+        assert_eq!(AnswerPipeline::from_config(&disabled).apply(fenced), fenced);
+    }
+
+    fn trim(max_chars: usize, answer: &str) -> String {
+        TrimAnswer {
+            max_chars,
+            note: "(trimmed)".into(),
+        }
+        .apply(answer.into())
+    }
+
+    #[test]
+    fn trim_prefers_paragraph_then_sentence_boundaries() {
+        let answer = "First paragraph here.\n\nSecond one. It goes on and on.";
+        assert_eq!(trim(40, answer), "First paragraph here.\n\n(trimmed)");
+        assert_eq!(
+            trim(52, answer),
+            "First paragraph here.\n\nSecond one.\n\n(trimmed)"
+        );
+        assert_eq!(
+            trim(15, "alpha beta gamma delta"),
+            "alpha beta\n\n(trimmed)"
+        );
+        assert_eq!(trim(60, answer), answer);
+
+        let sentences = "One sentence. Another sentence that is long.";
+        assert_eq!(trim(24, sentences), "One sentence.\n\n(trimmed)");
+    }
+
+    #[test]
+    fn trim_never_splits_words_chars_or_markup() {
+        // No break in the second half: a hard cut on a char boundary.
+        assert_eq!(trim(3, "ñññññ"), "ñññ\n\n(trimmed)");
+
+        assert_eq!(
+            trim(28, "Some <b>bold text that keeps going</b> on"),
+            "Some <b>bold text that</b>\n\n(trimmed)"
+        );
+        assert_eq!(trim(12, "Look at <a href=x>"), "Look at\n\n(trimmed)");
+        assert_eq!(
+            trim(26, "Code:\n```rust\nlet a = 1;\nlet b = 2;\n```"),
+            "Code:\n```rust\nlet a = 1;\n```\n\n(trimmed)"
+        );
+    }
+
+    #[test]
+    fn config_trims_before_escaping() {
+        let cfg = AnswerConfig {
+            max_chars: 10,
+            ..AnswerConfig::default()
+        };
+        let out = AnswerPipeline::from_config(&cfg).apply("a < b and more text");
+        assert_eq!(out, "a &lt; b and\n\n…(truncated)");
+    }
+
+    #[test]
+    fn default_config_only_escapes() {
+        let out = AnswerPipeline::from_config(&AnswerConfig::default()).apply("a < b");
+        assert_eq!(out, "a &lt; b");
+    }
+
+    #[test]
+    fn profiles_constrain_length_and_markup() {
+        let cfg = AnswerConfig::default();
+        let long = "word ".repeat(400);
+        let answer = format!("<b>Title</b> & {long}");
+
+        let full = AnswerPipeline::for_profile(&cfg, AnswerProfile::Conversational).apply(&answer);
+        assert!(full.starts_with("<b>Title</b> &amp; "));
+        assert!(!full.contains("truncated"));
+        assert_eq!(
+            AnswerPipeline::for_profile(&cfg, AnswerProfile::Structured).apply(&answer),
+            full
+        );
+
+        let terse = AnswerPipeline::for_profile(&cfg, AnswerProfile::Terse).apply(&answer);
+        // Escaping may lengthen the markup, never the text the reader sees.
+        let note = truncation_indicator(&cfg.truncation_label, Some(ParseMode::Html));
+        let note_len = "\n\n".len() + note.chars().count();
+        assert!(
+            html_to_plain_text(&terse).chars().count()
+                <= AnswerProfile::Terse.max_chars() + note_len
+        );
+        assert!(terse.starts_with("<b>Title</b>") && terse.ends_with("…(truncated)"));
+
+        let plain = AnswerPipeline::for_profile(&cfg, AnswerProfile::Plain).apply(&answer);
+        assert!(plain.starts_with("Title &amp; word"));
+        assert!(!plain.contains('<'));
+
+        // A tighter MAX_ANSWER_CHARS still applies to a terse profile.
+        let tight = AnswerConfig {
+            max_chars: 100,
+            ..AnswerConfig::default()
+        };
+        let out = AnswerPipeline::for_profile(&tight, AnswerProfile::Terse).apply(&answer);
+        assert!(html_to_plain_text(&out).chars().count() <= 100 + note_len);
+
+        assert!(AnswerProfile::Structured.show_sources());
+        assert!(!AnswerProfile::Conversational.show_sources());
+        assert!(!AnswerProfile::Plain.html());
+    }
+
+    #[test]
+    fn truncation_label_stays_literal_text() {
+        let cfg = AnswerConfig {
+            truncation_label: "<cut> & more".into(),
+            max_chars: 100,
+            ..AnswerConfig::default()
+        };
+        let answer = "word ".repeat(400);
+
+        for profile in [AnswerProfile::Terse, AnswerProfile::Plain] {
+            let out = AnswerPipeline::for_profile(&cfg, profile).apply(&answer);
+            assert!(
+                out.ends_with("\n\n&lt;cut&gt; &amp; more"),
+                "{profile:?}: {out}"
+            );
+            assert!(html_to_plain_text(&out).ends_with("<cut> & more"));
+        }
+    }
+}
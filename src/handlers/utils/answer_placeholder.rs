@@ -0,0 +1,84 @@
+// "…" messages sent while a command works, later edited into its answer or error.
+//
+// Useful where chat actions aren't shown (channels, some clients): the reader sees the
+// bot picked the command up and the result lands in the same message.
+
+use super::send_reply_or_plain;
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, sync::Mutex};
+use teloxide::{
+    prelude::*,
+    types::{ChatId, MessageId, ParseMode},
+};
+use tracing::warn;
+
+const PLACEHOLDER_TEXT: &str = "…";
+
+// (chat, user's message) -> placeholder waiting for the answer.
+static PLACEHOLDERS: Lazy<Mutex<HashMap<(ChatId, MessageId), MessageId>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn remember_placeholder(chat_id: ChatId, msg_id: MessageId, placeholder: MessageId) {
+    if let Ok(mut map) = PLACEHOLDERS.lock() {
+        map.insert((chat_id, msg_id), placeholder);
+    }
+}
+
+// Remove and return the placeholder answering `msg_id`, if one was sent.
+pub fn take_placeholder(chat_id: ChatId, msg_id: MessageId) -> Option<MessageId> {
+    PLACEHOLDERS.lock().ok()?.remove(&(chat_id, msg_id))
+}
+
+// Reply to `msg` with a placeholder. Edited commands skip it: their previous answer is
+// updated in place instead. A failed send only costs the placeholder.
+pub async fn send_placeholder(bot: &Bot, msg: &Message) {
+    if msg.edit_date().is_some() {
+        return;
+    }
+    match send_reply_or_plain(bot, msg, PLACEHOLDER_TEXT, true, false, None).await {
+        Ok(sent) => remember_placeholder(msg.chat.id, msg.id, sent.id),
+        Err(e) => warn!("Sending the answer placeholder failed: {e}"),
+    }
+}
+
+// Replace the placeholder with `text`; on failure it is deleted and None returned so the
+// caller sends a fresh message.
+pub async fn fill_placeholder(
+    bot: &Bot,
+    chat_id: ChatId,
+    placeholder: MessageId,
+    text: &str,
+    parse_html: bool,
+) -> Option<Message> {
+    let mut req = bot.edit_message_text(chat_id, placeholder, text);
+    if parse_html {
+        req = req.parse_mode(ParseMode::Html);
+    }
+    match req.await {
+        Ok(edited) => Some(edited),
+        Err(e) => {
+            warn!("Editing placeholder {placeholder} failed, sending a new message: {e}");
+            if let Err(e) = bot.delete_message(chat_id, placeholder).await {
+                warn!("Deleting placeholder {placeholder} failed: {e}");
+            }
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{remember_placeholder, take_placeholder};
+    use teloxide::types::{ChatId, MessageId};
+
+    #[test]
+    fn placeholders_are_taken_once_per_message() {
+        let chat = ChatId(-780000001);
+
+        remember_placeholder(chat, MessageId(1), MessageId(2));
+        assert_eq!(take_placeholder(chat, MessageId(2)), None);
+        assert_eq!(take_placeholder(ChatId(-780000002), MessageId(1)), None);
+        assert_eq!(take_placeholder(chat, MessageId(1)), Some(MessageId(2)));
+        assert_eq!(take_placeholder(chat, MessageId(1)), None);
+    }
+}
@@ -1,5 +1,6 @@
 // Manager that keeps a chat action being sent periodically.
 
+use super::{is_thread_permission_error, warn_thread_fallback};
 use teloxide::{
     prelude::*,
     types::{ChatAction, ChatId, ThreadId},
@@ -31,6 +32,8 @@ impl ChatActionKeepAlive {
     ) -> Self {
         let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
         let handle = tokio::spawn(async move {
+            // Dropped after a thread permission error so the action goes to the general topic.
+            let mut thread_id = thread_id;
             let mut ticker = interval(Duration::from_secs(interval_secs));
             loop {
                 tokio::select! {
@@ -42,8 +45,13 @@ impl ChatActionKeepAlive {
                             bot.send_chat_action(chat_id, action)
                         };
 
-                        if let Err(err) = send_req.await {
-                            tracing::warn!("send_chat_action failed: {:?}", err);
+                        match send_req.await {
+                            Err(err) if thread_id.is_some() && is_thread_permission_error(&err) => {
+                                warn_thread_fallback(chat_id);
+                                thread_id = None;
+                            }
+                            Err(err) => tracing::warn!("send_chat_action failed: {:?}", err),
+                            Ok(_) => {}
                         }
                     }
 
@@ -61,6 +69,14 @@ impl ChatActionKeepAlive {
         }
     }
 
+    // A keep-alive that sends nothing, for when a placeholder message stands in for the action.
+    pub fn idle() -> Self {
+        Self {
+            stop_tx: None,
+            handle: None,
+        }
+    }
+
     // Gracefully stop the background task and await its completion.
     pub async fn shutdown(&mut self) {
         if let Some(tx) = self.stop_tx.take() {
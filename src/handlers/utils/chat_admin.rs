@@ -0,0 +1,29 @@
+// Admin check for commands that change settings shared by the whole chat.
+
+use teloxide::prelude::*;
+use tracing::warn;
+
+// True in private chats, for anonymous admins (they post as the chat itself) and for
+// members Telegram reports as administrators or the owner.
+pub async fn sender_is_chat_admin(bot: &Bot, msg: &Message) -> bool {
+    if msg.chat.is_private() {
+        return true;
+    }
+    if msg
+        .sender_chat
+        .as_ref()
+        .is_some_and(|chat| chat.id == msg.chat.id)
+    {
+        return true;
+    }
+    let Some(user) = msg.from.as_ref() else {
+        return false;
+    };
+    match bot.get_chat_member(msg.chat.id, user.id).await {
+        Ok(member) => member.is_privileged(),
+        Err(e) => {
+            warn!("Could not check admin status in chat {}: {e}", msg.chat.id);
+            false
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// Per chat type command policy: operators can disable commands in private chats, groups
+// (including supergroups) or channels.
+
+use crate::config::ChatPolicyConfig;
+use teloxide::types::Chat;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChatScope {
+    Private,
+    Group,
+    Channel,
+}
+
+impl ChatScope {
+    pub fn of(chat: &Chat) -> Self {
+        if chat.is_private() {
+            ChatScope::Private
+        } else if chat.is_channel() {
+            ChatScope::Channel
+        } else {
+            ChatScope::Group
+        }
+    }
+}
+
+// Refusal for `command` in `scope`, or None if it is allowed there.
+pub fn command_refusal(cfg: &ChatPolicyConfig, scope: ChatScope, command: &str) -> Option<String> {
+    let disabled = match scope {
+        ChatScope::Private => &cfg.private_disabled,
+        ChatScope::Group => &cfg.group_disabled,
+        ChatScope::Channel => &cfg.channel_disabled,
+    };
+    if !disabled.iter().any(|c| c == command) {
+        return None;
+    }
+
+    Some(match scope {
+        ChatScope::Private => format!("/{command} isn't available in private chats."),
+        ChatScope::Group => {
+            format!("/{command} is disabled in groups, send it to me in a private chat instead.")
+        }
+        ChatScope::Channel => format!("/{command} isn't available in channels."),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChatScope, command_refusal};
+    use crate::config::ChatPolicyConfig;
+
+    fn policy() -> ChatPolicyConfig {
+        ChatPolicyConfig {
+            private_disabled: vec!["dollar".into()],
+            group_disabled: vec!["search".into(), "say".into()],
+            channel_disabled: vec!["ask".into()],
+        }
+    }
+
+    #[test]
+    fn private_chats_follow_their_list() {
+        let cfg = policy();
+        assert!(command_refusal(&cfg, ChatScope::Private, "search").is_none());
+        let refusal = command_refusal(&cfg, ChatScope::Private, "dollar").unwrap();
+        assert!(refusal.contains("private chats"));
+    }
+
+    #[test]
+    fn groups_point_to_a_private_chat() {
+        let cfg = policy();
+        assert!(command_refusal(&cfg, ChatScope::Group, "ask").is_none());
+        let refusal = command_refusal(&cfg, ChatScope::Group, "say").unwrap();
+        assert!(refusal.starts_with("/say is disabled in groups"));
+        assert!(refusal.contains("private chat"));
+    }
+
+    #[test]
+    fn channels_follow_their_list() {
+        let cfg = policy();
+        assert!(command_refusal(&cfg, ChatScope::Channel, "dollar").is_none());
+        let refusal = command_refusal(&cfg, ChatScope::Channel, "ask").unwrap();
+        assert!(refusal.contains("channels"));
+    }
+
+    #[test]
+    fn empty_policy_allows_everything() {
+        let cfg = ChatPolicyConfig::default();
+        for scope in [ChatScope::Private, ChatScope::Group, ChatScope::Channel] {
+            assert!(command_refusal(&cfg, scope, "search").is_none());
+        }
+    }
+}
@@ -0,0 +1,108 @@
+// Per-chat settings stored on the `chats` row of the whole chat (see settings_chat_id).
+
+use sqlx::PgPool;
+use teloxide::types::Message;
+
+// Settings belong to the chat itself, never to a forum topic: thread ids are small and
+// repeat across forums, so keying on them would leak a setting into other chats.
+pub fn settings_chat_id(msg: &Message) -> i64 {
+    msg.chat.id.0
+}
+
+// Whether /ask loads and saves history in this chat. Chats without a row use the default (on).
+pub async fn ask_memory_enabled(pool: &PgPool, chat_id: i64) -> Result<bool, sqlx::Error> {
+    let enabled = sqlx::query_scalar!(
+        "SELECT ask_memory FROM chats WHERE telegram_id = $1",
+        chat_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(enabled.unwrap_or(true))
+}
+
+// Turn /ask memory on or off, creating the chat row if needed.
+pub async fn set_ask_memory(pool: &PgPool, chat_id: i64, enabled: bool) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO chats (telegram_id, ask_memory)
+        VALUES ($1, $2)
+        ON CONFLICT (telegram_id) DO UPDATE SET ask_memory = EXCLUDED.ask_memory
+        "#,
+        chat_id,
+        enabled
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Commands disabled in this chat with /disable. Chats without a row have none.
+pub async fn disabled_commands(pool: &PgPool, chat_id: i64) -> Result<Vec<String>, sqlx::Error> {
+    let disabled = sqlx::query_scalar!(
+        "SELECT disabled_commands FROM chats WHERE telegram_id = $1",
+        chat_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(disabled.unwrap_or_default())
+}
+
+// Add `command` to (or remove it from) the chat's disabled commands, creating the row if needed.
+pub async fn set_command_disabled(
+    pool: &PgPool,
+    chat_id: i64,
+    command: &str,
+    disabled: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO chats (telegram_id, disabled_commands)
+        VALUES ($1, CASE WHEN $3 THEN ARRAY[$2::text] ELSE '{}'::text[] END)
+        ON CONFLICT (telegram_id) DO UPDATE SET disabled_commands = CASE
+            WHEN $3 THEN array_append(array_remove(chats.disabled_commands, $2::text), $2::text)
+            ELSE array_remove(chats.disabled_commands, $2::text)
+        END
+        "#,
+        chat_id,
+        command,
+        disabled
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::settings_chat_id;
+    use serde_json::json;
+    use teloxide::types::Message;
+
+    fn forum_message(chat_id: i64, thread_id: i32) -> Message {
+        serde_json::from_value(json!({
+            "message_id": 9,
+            "message_thread_id": thread_id,
+            "is_topic_message": true,
+            "date": 1_700_000_000,
+            "chat": {"id": chat_id, "type": "supergroup", "title": "Forum", "is_forum": true},
+            "from": {"id": 42, "is_bot": false, "first_name": "Ana"},
+            "text": "/disable search",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn forums_sharing_a_thread_id_keep_separate_settings() {
+        let first = forum_message(-1001111111111, 5);
+        let second = forum_message(-1002222222222, 5);
+        assert_eq!(first.thread_id, second.thread_id);
+
+        assert_eq!(settings_chat_id(&first), -1001111111111);
+        assert_eq!(settings_chat_id(&second), -1002222222222);
+        // Any topic of the same forum shares the group's settings.
+        assert_eq!(
+            settings_chat_id(&forum_message(-1001111111111, 77)),
+            settings_chat_id(&first)
+        );
+    }
+}
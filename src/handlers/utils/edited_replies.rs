@@ -0,0 +1,118 @@
+// Replies sent for recent commands, so editing the command can update them in place.
+//
+// Only answers of the last `window` are kept: older edits are ignored and their entries
+// are dropped whenever a new reply is recorded, which bounds the map.
+
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use teloxide::types::{ChatId, MessageId};
+
+struct SentReplies {
+    replies: Vec<MessageId>,
+    at: Instant,
+}
+
+// (chat, user's message) -> bot messages answering it.
+static REPLIES: Lazy<Mutex<HashMap<(ChatId, MessageId), SentReplies>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Record the messages answering `msg_id`, forgetting replies older than `window`.
+pub fn remember_replies(
+    chat_id: ChatId,
+    msg_id: MessageId,
+    replies: Vec<MessageId>,
+    window: Duration,
+) {
+    if window.is_zero() || replies.is_empty() {
+        return;
+    }
+    if let Ok(mut map) = REPLIES.lock() {
+        map.retain(|_, sent| sent.at.elapsed() <= window);
+        map.insert(
+            (chat_id, msg_id),
+            SentReplies {
+                replies,
+                at: Instant::now(),
+            },
+        );
+    }
+}
+
+// True if `msg_id` was answered within `window`, i.e. an edit of it should be re-run.
+pub fn has_recent_replies(chat_id: ChatId, msg_id: MessageId, window: Duration) -> bool {
+    REPLIES
+        .lock()
+        .map(|map| {
+            map.get(&(chat_id, msg_id))
+                .is_some_and(|sent| sent.at.elapsed() <= window)
+        })
+        .unwrap_or(false)
+}
+
+// Remove and return the replies of `msg_id`, if still within `window`.
+pub fn take_replies(
+    chat_id: ChatId,
+    msg_id: MessageId,
+    window: Duration,
+) -> Option<Vec<MessageId>> {
+    let sent = REPLIES.lock().ok()?.remove(&(chat_id, msg_id))?;
+    (sent.at.elapsed() <= window).then_some(sent.replies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_recent_replies, remember_replies, take_replies};
+    use std::time::Duration;
+    use teloxide::types::{ChatId, MessageId};
+
+    #[test]
+    fn replies_are_tracked_within_the_window() {
+        let window = Duration::from_secs(60);
+        let chat = ChatId(-770000001);
+
+        remember_replies(chat, MessageId(1), vec![MessageId(2), MessageId(3)], window);
+        assert!(has_recent_replies(chat, MessageId(1), window));
+        assert!(!has_recent_replies(chat, MessageId(2), window));
+        assert!(!has_recent_replies(
+            ChatId(-770000002),
+            MessageId(1),
+            window
+        ));
+
+        assert_eq!(
+            take_replies(chat, MessageId(1), window),
+            Some(vec![MessageId(2), MessageId(3)])
+        );
+        assert_eq!(take_replies(chat, MessageId(1), window), None);
+
+        // A zero window disables tracking.
+        remember_replies(chat, MessageId(4), vec![MessageId(5)], Duration::ZERO);
+        assert!(!has_recent_replies(chat, MessageId(4), window));
+    }
+
+    #[test]
+    fn expired_replies_are_ignored() {
+        let chat = ChatId(-770000003);
+
+        remember_replies(
+            chat,
+            MessageId(1),
+            vec![MessageId(2)],
+            Duration::from_secs(60),
+        );
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!has_recent_replies(
+            chat,
+            MessageId(1),
+            Duration::from_millis(10)
+        ));
+        assert_eq!(
+            take_replies(chat, MessageId(1), Duration::from_millis(10)),
+            None
+        );
+    }
+}
@@ -1,21 +1,24 @@
 // Keep message/user extraction logic in one place so handlers stay thin.
 
+use crate::config::IdentityConfig;
 use teloxide::types::Message;
 
 // Extract and normalize core user/chat identifiers from a Telegram `Message`.
-pub fn extract_user_info(msg: &Message) -> Result<(i64, String, i64), String> {
-    // Get the sender object; if absent we can't proceed
-    let user = msg
-        .from
-        .as_ref()
-        .ok_or_else(|| "The user could not be identified.".to_string())?;
-
-    // Normalize id and language with sensible defaults
-    let user_id: i64 = user.id.0 as i64;
-    let user_lang = user
-        .language_code
-        .clone()
-        .unwrap_or_else(|| "en".to_string());
+pub fn extract_user_info(
+    msg: &Message,
+    cfg: &IdentityConfig,
+) -> Result<(i64, String, i64), String> {
+    // Anonymous admins and channels come with a placeholder bot in `from`, shared by
+    // everyone, so the chat they speak for identifies them instead.
+    let (user_id, lang) = match (msg.sender_chat.as_ref(), msg.from.as_ref()) {
+        (Some(chat), _) if cfg.sender_chat => (chat.id.0, None),
+        (_, Some(user)) => (user.id.0 as i64, user.language_code.clone()),
+        // No sender at all: nothing to attribute the message to.
+        _ => return Err("The user could not be identified.".to_string()),
+    };
+
+    // Normalize language with the configured default
+    let user_lang = lang.unwrap_or_else(|| cfg.default_lang.clone());
 
     // If message is inside a forum thread, prefer thread id (keeps history grouped)
     let msg_chat_id: i64 = msg
@@ -25,3 +28,76 @@ pub fn extract_user_info(msg: &Message) -> Result<(i64, String, i64), String> {
 
     Ok((user_id, user_lang, msg_chat_id))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::extract_user_info;
+    use crate::config::IdentityConfig;
+    use serde_json::json;
+    use teloxide::types::Message;
+
+    const GROUP_ID: i64 = -1001234567890;
+
+    fn group_message(from: serde_json::Value, sender_chat: Option<serde_json::Value>) -> Message {
+        let mut value = json!({
+            "message_id": 7,
+            "date": 1_700_000_000,
+            "chat": {"id": GROUP_ID, "type": "supergroup", "title": "Group"},
+            "from": from,
+            "text": "/ask hi",
+        });
+        if let Some(sender_chat) = sender_chat {
+            value["sender_chat"] = sender_chat;
+        }
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn anonymous_admin_is_identified_by_the_group() {
+        // Telegram's placeholder user for anonymous group admins.
+        let msg = group_message(
+            json!({"id": 1087968824, "is_bot": true, "first_name": "Group", "username": "GroupAnonymousBot"}),
+            Some(json!({"id": GROUP_ID, "type": "supergroup", "title": "Group"})),
+        );
+        let cfg = IdentityConfig {
+            sender_chat: true,
+            default_lang: "es".into(),
+        };
+
+        assert_eq!(
+            extract_user_info(&msg, &cfg).unwrap(),
+            (GROUP_ID, "es".to_string(), GROUP_ID)
+        );
+
+        // Disabled: the placeholder user is kept as before.
+        let cfg = IdentityConfig {
+            sender_chat: false,
+            ..cfg
+        };
+        assert_eq!(extract_user_info(&msg, &cfg).unwrap().0, 1087968824);
+    }
+
+    #[test]
+    fn messages_without_any_sender_are_refused() {
+        let mut value = serde_json::to_value(group_message(
+            json!({"id": 1, "is_bot": false, "first_name": "A"}),
+            None,
+        ))
+        .unwrap();
+        value.as_object_mut().unwrap().remove("from");
+        let msg: Message = serde_json::from_value(value).unwrap();
+        assert!(extract_user_info(&msg, &IdentityConfig::default()).is_err());
+    }
+
+    #[test]
+    fn regular_users_keep_their_id_and_language() {
+        let msg = group_message(
+            json!({"id": 42, "is_bot": false, "first_name": "Ana", "language_code": "pt-BR"}),
+            None,
+        );
+        assert_eq!(
+            extract_user_info(&msg, &IdentityConfig::default()).unwrap(),
+            (42, "pt-BR".to_string(), GROUP_ID)
+        );
+    }
+}
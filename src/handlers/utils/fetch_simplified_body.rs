@@ -1,18 +1,161 @@
+use super::ScrapeError;
+use crate::http::http_client;
 use html_escape::encode_text;
 use kuchiki::NodeRef;
 use kuchiki::traits::*;
 use reqwest;
+use std::time::Duration;
 
-pub async fn fetch_simplified_body(url: &str) -> Result<String, String> {
-    // Map reqwest errors to string descriptions
-    let raw = reqwest::get(url)
+// Deadline for fetching one page, body included.
+const PAGE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// OpenGraph metadata from the page head; every field is optional.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+}
+
+impl PageMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.description.is_none() && self.image.is_none()
+    }
+
+    // Lines prepended to the page in the model context; empty when no tag was found.
+    pub fn to_context(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+
+        let mut out = String::from("PageMetadata:\n");
+        for (name, value) in [
+            ("title", &self.title),
+            ("description", &self.description),
+            ("image", &self.image),
+        ] {
+            if let Some(v) = value {
+                out.push_str(&format!("og:{name}: {v}\n"));
+            }
+        }
+        out.push('\n');
+        out
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimplifiedPage {
+    pub body: String,
+    pub metadata: PageMetadata,
+}
+
+// Read og:title, og:description and og:image from the document's <meta> tags.
+pub fn parse_og_metadata(document: &NodeRef) -> PageMetadata {
+    let mut meta = PageMetadata::default();
+
+    let Ok(tags) = document.select("meta") else {
+        return meta;
+    };
+
+    for tag in tags {
+        let attrs = tag.attributes.borrow();
+        // Some sites use name= instead of property= for OpenGraph tags.
+        let Some(key) = attrs.get("property").or_else(|| attrs.get("name")) else {
+            continue;
+        };
+        let Some(content) = attrs
+            .get("content")
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+        else {
+            continue;
+        };
+
+        let slot = match key.to_ascii_lowercase().as_str() {
+            "og:title" => &mut meta.title,
+            "og:description" => &mut meta.description,
+            "og:image" => &mut meta.image,
+            _ => continue,
+        };
+        // Keep the first occurrence of each tag.
+        if slot.is_none() {
+            *slot = Some(content.to_string());
+        }
+    }
+
+    meta
+}
+
+// A fetched page with the raw response details kept for diagnostics.
+#[derive(Debug, Clone)]
+pub struct FetchedPage {
+    pub raw_len: usize,
+    pub content_type: Option<String>,
+    pub page: SimplifiedPage,
+}
+
+pub async fn fetch_page(url: &str) -> Result<FetchedPage, ScrapeError> {
+    fetch_page_within(url, PAGE_TIMEOUT).await
+}
+
+async fn fetch_page_within(url: &str, timeout: Duration) -> Result<FetchedPage, ScrapeError> {
+    let resp = http_client()
+        .get(url)
+        .timeout(timeout)
+        .send()
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| ScrapeError::from_reqwest(&e))?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(ScrapeError::Status(status.as_u16()));
+    }
+
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    if let Some(kind) = content_type.as_deref()
+        && !is_textual(kind)
+    {
+        return Err(ScrapeError::NonHtml(kind.to_string()));
+    }
+
+    let raw = resp
         .text()
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| ScrapeError::from_reqwest(&e))?;
+
+    Ok(FetchedPage {
+        raw_len: raw.len(),
+        content_type,
+        page: simplify_html(&raw),
+    })
+}
+
+// Images, PDFs and other binaries can't be simplified into text.
+fn is_textual(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    mime.starts_with("text/")
+        || mime.contains("html")
+        || mime.contains("xml")
+        || mime.contains("json")
+}
+
+pub async fn fetch_simplified_body(url: &str) -> Result<SimplifiedPage, ScrapeError> {
+    fetch_page(url).await.map(|fetched| fetched.page)
+}
 
+// Keep only text and basic formatting tags from the page body, plus its OpenGraph metadata.
+pub fn simplify_html(raw: &str) -> SimplifiedPage {
     let document = kuchiki::parse_html().one(raw);
+    let metadata = parse_og_metadata(&document);
 
     let root: NodeRef = match document.select_first("body") {
         Ok(node) => node.as_node().clone(),
@@ -84,7 +227,154 @@ pub async fn fetch_simplified_body(url: &str) -> Result<String, String> {
     let mut simplified = String::with_capacity(4096);
     walk(&root, &mut simplified);
 
-    let result = format!("<body>{}</body>", simplified.trim());
+    let body = format!("<body>{}</body>", simplified.trim());
+
+    SimplifiedPage { body, metadata }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PageMetadata, fetch_page_within, parse_og_metadata};
+    use crate::handlers::utils::ScrapeError;
+    use axum::{Router, http::StatusCode, http::header, routing::get};
+    use kuchiki::traits::*;
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    // Mock site: a page, a missing page, an image and a page that never answers.
+    async fn mock_site() -> std::net::SocketAddr {
+        let app = Router::new()
+            .route(
+                "/page",
+                get(|| async {
+                    (
+                        [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+                        "<html><body><p>Hello</p></body></html>",
+                    )
+                }),
+            )
+            .route(
+                "/missing",
+                get(|| async { (StatusCode::NOT_FOUND, "gone") }),
+            )
+            .route(
+                "/image",
+                get(|| async { ([(header::CONTENT_TYPE, "image/png")], vec![0u8; 8]) }),
+            )
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                    "late"
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        addr
+    }
+
+    #[tokio::test]
+    async fn fetches_html_pages() {
+        let addr = mock_site().await;
+        let fetched = fetch_page_within(&format!("http://{addr}/page"), WAIT)
+            .await
+            .unwrap();
+        assert_eq!(fetched.page.body, "<body><p>Hello </p></body>");
+    }
+
+    #[tokio::test]
+    async fn reports_http_status_and_content_type() {
+        let addr = mock_site().await;
+        assert_eq!(
+            fetch_page_within(&format!("http://{addr}/missing"), WAIT)
+                .await
+                .unwrap_err(),
+            ScrapeError::Status(404)
+        );
+        assert_eq!(
+            fetch_page_within(&format!("http://{addr}/image"), WAIT)
+                .await
+                .unwrap_err(),
+            ScrapeError::NonHtml("image/png".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_timeouts() {
+        let addr = mock_site().await;
+        let err = fetch_page_within(&format!("http://{addr}/slow"), Duration::from_millis(200))
+            .await
+            .unwrap_err();
+        assert_eq!(err, ScrapeError::Timeout);
+    }
+
+    #[tokio::test]
+    async fn reports_tls_failures() {
+        // A plain HTTP server can't complete a TLS handshake.
+        let addr = mock_site().await;
+        let err = fetch_page_within(&format!("https://{addr}/page"), WAIT)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ScrapeError::Tls(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn reports_dns_failures() {
+        let err = fetch_page_within("http://no-such-host.invalid/", WAIT)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ScrapeError::Dns(_)), "{err:?}");
+    }
 
-    Ok(result)
+    #[tokio::test]
+    async fn reports_truncated_bodies() {
+        // Promises 100 bytes, sends 5 and hangs up.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut buf).await;
+            let _ = socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 100\r\n\r\nshort")
+                .await;
+        });
+
+        let err = fetch_page_within(&format!("http://{addr}/"), WAIT)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ScrapeError::Body(_)), "{err:?}");
+    }
+
+    #[test]
+    fn reads_opengraph_tags_from_head() {
+        let doc = kuchiki::parse_html().one(
+            r#"<html><head>
+                <meta property="og:title" content="Rust 2024">
+                <meta name="og:description" content=" New edition ">
+                <meta property="og:image" content="https://e.io/cover.png">
+                <meta property="og:title" content="Ignored duplicate">
+            </head><body></body></html>"#,
+        );
+        assert_eq!(
+            parse_og_metadata(&doc),
+            PageMetadata {
+                title: Some("Rust 2024".into()),
+                description: Some("New edition".into()),
+                image: Some("https://e.io/cover.png".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn page_without_opengraph_has_empty_metadata() {
+        let doc = kuchiki::parse_html()
+            .one(r#"<html><head><meta charset="utf-8"><title>x</title></head></html>"#);
+        let meta = parse_og_metadata(&doc);
+        assert!(meta.is_empty());
+        assert_eq!(meta.to_context(), "");
+    }
 }
@@ -0,0 +1,30 @@
+// Edited commands run again: the turn saved by the original run gives way to the new one.
+
+use sqlx::PgPool;
+
+// Soft-delete the history row saved for command message `message_id` of this user/chat.
+// Returns how many rows were removed (0 when the original run saved nothing).
+pub async fn forget_edited_turn(
+    pool: &PgPool,
+    user_id: i64,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<u64, sqlx::Error> {
+    let res = sqlx::query!(
+        r#"
+        UPDATE messages
+        SET deleted_at = now()
+        WHERE user_telegram_id = $1
+          AND chat_telegram_id = $2
+          AND message_telegram_id = $3
+          AND deleted_at IS NULL
+        "#,
+        user_id,
+        chat_id,
+        message_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(res.rows_affected())
+}
@@ -0,0 +1,50 @@
+// Relative timestamps ("2 hours ago") for history turns shown to the model.
+
+// Describe an age in seconds with its largest whole unit.
+pub fn format_age(secs: i64) -> String {
+    const UNITS: [(i64, &str); 4] = [
+        (86_400 * 7, "week"),
+        (86_400, "day"),
+        (3_600, "hour"),
+        (60, "minute"),
+    ];
+
+    for (size, unit) in UNITS {
+        let n = secs / size;
+        if n >= 1 {
+            let plural = if n == 1 { "" } else { "s" };
+            return format!("{n} {unit}{plural} ago");
+        }
+    }
+    "just now".to_string()
+}
+
+// Prefix a history turn with its age, e.g. "[2 hours ago] what time is it?".
+pub fn with_age(content: &str, age_secs: Option<i64>) -> String {
+    match age_secs {
+        Some(secs) => format!("[{}] {content}", format_age(secs)),
+        None => content.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_age, with_age};
+
+    #[test]
+    fn formats_with_the_largest_unit() {
+        assert_eq!(format_age(-5), "just now");
+        assert_eq!(format_age(59), "just now");
+        assert_eq!(format_age(60), "1 minute ago");
+        assert_eq!(format_age(150), "2 minutes ago");
+        assert_eq!(format_age(2 * 3_600 + 1_800), "2 hours ago");
+        assert_eq!(format_age(86_400), "1 day ago");
+        assert_eq!(format_age(20 * 86_400), "2 weeks ago");
+    }
+
+    #[test]
+    fn prefixes_only_known_ages() {
+        assert_eq!(with_age("hi", Some(3_600)), "[1 hour ago] hi");
+        assert_eq!(with_age("hi", None), "hi");
+    }
+}
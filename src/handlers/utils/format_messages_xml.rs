@@ -104,10 +104,12 @@ mod tests {
             MessageRow {
                 content: Some("Hola & <mundo>".into()),
                 ia_response: Some("Bien > todo".into()),
+                age_secs: None,
             },
             MessageRow {
                 content: Some("Mensaje con ]]> dentro: ]]>!".into()),
                 ia_response: None,
+                age_secs: None,
             },
         ];
 
@@ -0,0 +1,25 @@
+// Turns the bot's Telegram HTML into plain text, for speech, files and plain answers.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+
+// Drop the tags and decode the entities of a Telegram HTML text.
+pub fn html_to_plain_text(html: &str) -> String {
+    let without_tags = TAG_RE.replace_all(html, "");
+    html_escape::decode_html_entities(&without_tags)
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::html_to_plain_text;
+
+    #[test]
+    fn strips_tags_and_decodes_entities() {
+        let out = html_to_plain_text("📌 <b>Title</b>\n<code>1 &lt; 2</code> &amp; more");
+        assert_eq!(out, "📌 Title\n1 < 2 & more");
+    }
+}
@@ -1,7 +1,9 @@
 // Keep these small and testable: other handlers can call them directly.
 
+use super::{rate_limit_retry_after, with_model_timeout};
 use groqai::{ChatMessage, GroqClient, MessageContent, Role};
-use tracing::error;
+use std::time::Duration;
+use tracing::{error, warn};
 
 // Run the "reasoning" / preprocessing model with a minimal retry strategy.
 pub async fn run_reasoning_step(
@@ -9,6 +11,7 @@ pub async fn run_reasoning_step(
     base_prompt: &str,
     reasoning_model: &str,
     system_prompt: String,
+    timeout: Duration,
 ) -> Option<String> {
     // Build a simple conversation for the reasoning model
     let reasoning_user = format!("Full prompt+history:\n\n{base_prompt}");
@@ -19,13 +22,15 @@ pub async fn run_reasoning_step(
 
     // Up to 2 attempts: useful for transient model hiccups or non-text outputs
     for attempt in 0..2 {
-        match groq
-            .chat(reasoning_model)
-            .messages(messages.clone())
-            .max_completion_tokens(2000)
-            .temperature(0.0)
-            .send()
-            .await
+        match with_model_timeout(
+            timeout,
+            groq.chat(reasoning_model)
+                .messages(messages.clone())
+                .max_completion_tokens(2000)
+                .temperature(0.0)
+                .send(),
+        )
+        .await
         {
             Ok(resp) => {
                 // Prefer textual outputs; trim whitespace
@@ -40,6 +45,12 @@ pub async fn run_reasoning_step(
             Err(e) => {
                 // Log and abort: caller needs to handle rollback/notify user
                 error!("Reasoning model error (attempt {}): {e}", attempt);
+                if let Some(wait) = rate_limit_retry_after(&e) {
+                    warn!(
+                        "Reasoning model rate limited, retry after {}s",
+                        wait.as_secs_f32()
+                    );
+                }
                 return None;
             }
         }
@@ -55,20 +66,23 @@ pub async fn run_main_model(
     prompt_for_main: &str,
     main_model: &str,
     system_prompt: String,
+    timeout: Duration,
 ) -> Result<String, String> {
     let messages = vec![
         ChatMessage::new_text(Role::System, system_prompt),
         ChatMessage::new_text(Role::User, prompt_for_main.to_string()),
     ];
 
-    let resp = groq
-        .chat(main_model)
-        .messages(messages)
-        .max_completion_tokens(3000)
-        .temperature(0.0)
-        .send()
-        .await
-        .map_err(|e| format!("Main model error: {e}"))?;
+    let resp = with_model_timeout(
+        timeout,
+        groq.chat(main_model)
+            .messages(messages)
+            .max_completion_tokens(3000)
+            .temperature(0.0)
+            .send(),
+    )
+    .await
+    .map_err(|e| format!("Main model error: {e}"))?;
 
     // Return trimmed text (handler will perform Telegram escaping before sending)
     if let MessageContent::Text(text) = &resp.choices[0].message.content {
@@ -1,17 +1,32 @@
 // Image analysis helper that downloads a Telegram photo and sends it to a vision LLM.
 
-use crate::handlers::types::MessageRow;
+use super::with_model_timeout;
+use crate::{config::Models, handlers::types::MessageRow, http::http_client};
 use base64::{Engine as _, engine::general_purpose};
 use groqai::{ChatMessage, GroqClient, ImageUrl, MessageContent, MessagePart, Role};
-use reqwest::Client;
 use serde_json::{Value, json};
+use std::time::Duration;
 use teloxide::{
     prelude::*,
     types::{FileId, Message},
 };
-use tracing::error;
+use tracing::{error, warn};
 
-// Analyzes a Telegram image using a vision model, guided by the user prompt.
+// Groq rejects requests whose base64-encoded image exceeds 4 MB, with an opaque error.
+const MAX_INLINE_IMAGE_BYTES: usize = 4 * 1024 * 1024;
+
+// True if an inline data URL is over the provider's limit and must not be sent.
+fn inline_image_too_large(data_url: &str) -> bool {
+    data_url.len() > MAX_INLINE_IMAGE_BYTES
+}
+
+// User-facing error for a photo the provider won't accept inline.
+const IMAGE_TOO_LARGE: &str =
+    "The image is too large to analyze, send a smaller or compressed photo.";
+
+// Analyzes a Telegram image using a vision model, guided by the user prompt. A photo over
+// the provider's size limit is an error for the user instead of a note to the model,
+// which would otherwise answer about an image it never saw.
 pub async fn analyze_image(
     bot: &Bot,
     msg: &Message,
@@ -19,8 +34,8 @@ pub async fn analyze_image(
     system_prompt: &str,
     history: Vec<MessageRow>,
     groq: &GroqClient,
-    vision_model: &str,
-) -> String {
+    models: &Models,
+) -> Result<String, &'static str> {
     // Vision-capable model identifier.
     let mut image_section = String::new();
 
@@ -37,6 +52,14 @@ pub async fn analyze_image(
                     let img_b64 = general_purpose::STANDARD.encode(&img_bytes);
                     let data_url = format!("data:{};base64,{}", mime, img_b64);
 
+                    if inline_image_too_large(&data_url) {
+                        warn!(
+                            "Image too large to inline ({} bytes encoded), skipping analysis",
+                            data_url.len()
+                        );
+                        return Err(IMAGE_TOO_LARGE);
+                    }
+
                     let mut convo: Vec<ChatMessage> = Vec::new();
                     // System prompt for the vision model.
                     convo.push(ChatMessage::new_text(Role::System, system_prompt));
@@ -72,13 +95,15 @@ pub async fn analyze_image(
                     convo.push(vision_msg);
 
                     // Send request to the vision model.
-                    match groq
-                        .chat(vision_model)
-                        .messages(convo)
-                        .max_completion_tokens(1200)
-                        .temperature(0.2)
-                        .send()
-                        .await
+                    match with_model_timeout(
+                        Duration::from_secs(models.vision_timeout_secs),
+                        groq.chat(&models.vision)
+                            .messages(convo)
+                            .max_completion_tokens(1200)
+                            .temperature(0.2)
+                            .send(),
+                    )
+                    .await
                     {
                         Ok(vresp) => {
                             // Take the first model choice, if any.
@@ -143,7 +168,7 @@ pub async fn analyze_image(
         }
     }
 
-    image_section
+    Ok(image_section)
 }
 
 // Returns the FileId of the largest available photo in the message.
@@ -185,7 +210,7 @@ async fn download_telegram_file_bytes(
 ) -> Result<Vec<u8>, reqwest::Error> {
     let token = bot.token().to_string();
     let url = format!("https://api.telegram.org/file/bot{}/{}", token, file_path);
-    let resp = Client::new().get(&url).send().await?;
+    let resp = http_client().get(&url).send().await?;
     let bytes = resp.bytes().await?;
     Ok(bytes.to_vec())
 }
@@ -256,3 +281,23 @@ fn extract_text_from_message_content(mc: &MessageContent) -> Option<String> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_INLINE_IMAGE_BYTES, inline_image_too_large};
+    use base64::{Engine as _, engine::general_purpose};
+
+    #[test]
+    fn rejects_oversized_inline_images() {
+        let small = general_purpose::STANDARD.encode(vec![0u8; 1024]);
+        assert!(!inline_image_too_large(&format!(
+            "data:image/jpeg;base64,{small}"
+        )));
+
+        // 3.5 MB of raw bytes grows past the 4 MB limit once base64-encoded.
+        let large = general_purpose::STANDARD.encode(vec![0u8; 3_500_000]);
+        let data_url = format!("data:image/jpeg;base64,{large}");
+        assert!(data_url.len() > MAX_INLINE_IMAGE_BYTES);
+        assert!(inline_image_too_large(&data_url));
+    }
+}
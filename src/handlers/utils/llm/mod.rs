@@ -3,3 +3,29 @@ pub use image::{analyze_image, message_has_photo};
 
 pub mod analize;
 pub use analize::{run_main_model, run_reasoning_step};
+
+pub mod summarize;
+pub use summarize::summarize_chunks;
+
+pub mod thinking_budget;
+pub use thinking_budget::{
+    reasoning_effort_for, suggest_thinking_budget, supports_reasoning_effort,
+};
+
+pub mod tts;
+pub use tts::synthesize_speech;
+
+pub mod model_alias;
+pub use model_alias::take_model_alias;
+
+pub mod model_chain;
+pub use model_chain::{is_retryable_model_error, run_model_chain};
+
+pub mod rate_limit;
+pub use rate_limit::{is_rate_limit_error, model_error_message, rate_limit_retry_after};
+
+pub mod temperature;
+pub use temperature::clamp_temperature;
+
+pub mod timeout;
+pub use timeout::{last_model_call, with_model_timeout};
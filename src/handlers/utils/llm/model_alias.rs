@@ -0,0 +1,89 @@
+// Inline model choice for /ask: a leading "[alias]" picks one of the MODEL_ALIASES.
+
+use crate::config::ChainModel;
+use std::collections::HashMap;
+
+// Remove a leading `[alias]` from `text` and return its model. Without configured
+// aliases the text is left alone, and so is a bracket that can't be an alias name
+// (e.g. "[1, 2, 3] sort this").
+pub fn take_model_alias(
+    text: &str,
+    aliases: &HashMap<String, ChainModel>,
+) -> Result<(String, Option<ChainModel>), String> {
+    let trimmed = text.trim_start();
+    let Some((name, rest)) = trimmed
+        .strip_prefix('[')
+        .and_then(|after| after.split_once(']'))
+    else {
+        return Ok((text.to_string(), None));
+    };
+    let is_alias_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if aliases.is_empty() || !is_alias_name {
+        return Ok((text.to_string(), None));
+    }
+
+    match aliases.get(&name.to_lowercase()) {
+        Some(model) => Ok((rest.trim_start().to_string(), Some(model.clone()))),
+        None => {
+            let mut known: Vec<String> = aliases.keys().map(|a| format!("[{a}]")).collect();
+            known.sort();
+            Err(format!(
+                "Unknown model [{name}]. Available: {}.",
+                known.join(", ")
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::take_model_alias;
+    use crate::config::{ChainModel, Provider};
+    use std::collections::HashMap;
+
+    fn aliases() -> HashMap<String, ChainModel> {
+        HashMap::from([
+            (
+                "pro".to_string(),
+                ChainModel {
+                    provider: Provider::Groq,
+                    model: "openai/gpt-oss-120b".into(),
+                },
+            ),
+            (
+                "fast".to_string(),
+                ChainModel {
+                    provider: Provider::Groq,
+                    model: "llama-3.1-8b-instant".into(),
+                },
+            ),
+        ])
+    }
+
+    #[test]
+    fn picks_the_model_of_a_leading_alias() {
+        let (text, model) =
+            take_model_alias("[Pro] explain quantum tunneling", &aliases()).unwrap();
+        assert_eq!(text, "explain quantum tunneling");
+        assert_eq!(model.unwrap().model, "openai/gpt-oss-120b");
+
+        // No prefix, or a bracket that isn't an alias name: default chain.
+        let (text, model) = take_model_alias("explain [pro] this", &aliases()).unwrap();
+        assert_eq!((text.as_str(), model), ("explain [pro] this", None));
+        let (text, model) = take_model_alias("[1, 2, 3] sort this", &aliases()).unwrap();
+        assert_eq!((text.as_str(), model), ("[1, 2, 3] sort this", None));
+        let (_, model) = take_model_alias("[pro] hi", &HashMap::new()).unwrap();
+        assert_eq!(model, None);
+    }
+
+    #[test]
+    fn rejects_unknown_aliases() {
+        assert_eq!(
+            take_model_alias("[ultra] hi", &aliases()).unwrap_err(),
+            "Unknown model [ultra]. Available: [fast], [pro]."
+        );
+    }
+}
@@ -0,0 +1,102 @@
+// Ordered model fallback: try each configured model until one answers.
+
+use super::{clamp_temperature, supports_reasoning_effort, with_model_timeout};
+use crate::config::{ChainModel, Provider};
+use groqai::{ChatMessage, GroqClient, MessageContent};
+use std::time::Duration;
+use tracing::{info, warn};
+
+// Errors that another model could avoid: timeouts, rate limits, overloaded or
+// unavailable models. Auth failures and malformed requests would fail everywhere.
+pub fn is_retryable_model_error(err: &str) -> bool {
+    let err = err.to_lowercase();
+
+    let fatal = ["401", "403", "unauthorized", "forbidden", "invalid api key"];
+    if fatal.iter().any(|f| err.contains(f)) {
+        return false;
+    }
+    // A 400 naming the model (decommissioned, unknown) is worth retrying elsewhere.
+    if (err.contains("400") || err.contains("invalid_request")) && !err.contains("model") {
+        return false;
+    }
+    true
+}
+
+// Send `messages` to each model of `chain` in order. Returns the answer text and
+// the model that produced it, or the last error once the chain is exhausted.
+// `reasoning_effort` is only sent to the models that accept it.
+pub async fn run_model_chain(
+    groq: &GroqClient,
+    chain: &[ChainModel],
+    messages: Vec<ChatMessage>,
+    max_tokens: u32,
+    temperature: f32,
+    reasoning_effort: Option<&str>,
+    timeout: Duration,
+) -> Result<(String, String), String> {
+    let mut last_err = String::from("no model configured");
+
+    for (idx, entry) in chain.iter().enumerate() {
+        let res = match entry.provider {
+            Provider::Groq => {
+                let mut request = groq
+                    .chat(&entry.model)
+                    .messages(messages.clone())
+                    .max_completion_tokens(max_tokens)
+                    .temperature(clamp_temperature(entry.provider, temperature));
+                if let Some(effort) =
+                    reasoning_effort.filter(|_| supports_reasoning_effort(&entry.model))
+                {
+                    request = request.reasoning_effort(effort.to_string());
+                }
+                with_model_timeout(timeout, request.send()).await
+            }
+        };
+
+        match res {
+            Ok(resp) => {
+                let text = match &resp.choices[0].message.content {
+                    MessageContent::Text(text) => text.trim().to_string(),
+                    _ => String::new(),
+                };
+                info!(
+                    "Answered by {:?}:{} ({})",
+                    entry.provider,
+                    entry.model,
+                    idx + 1
+                );
+                return Ok((text, entry.model.clone()));
+            }
+            Err(e) if is_retryable_model_error(&e) && idx + 1 < chain.len() => {
+                warn!("Model {} failed, trying the next one: {e}", entry.model);
+                last_err = e;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(last_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_retryable_model_error;
+
+    #[test]
+    fn classifies_model_errors() {
+        assert!(is_retryable_model_error("model call timed out after 60s"));
+        assert!(is_retryable_model_error(
+            "429 Too Many Requests: rate limit"
+        ));
+        assert!(is_retryable_model_error("503 Service Unavailable"));
+        assert!(is_retryable_model_error(
+            "400 Bad Request: the model `x` has been decommissioned"
+        ));
+        assert!(!is_retryable_model_error(
+            "401 Unauthorized: Invalid API Key"
+        ));
+        assert!(!is_retryable_model_error(
+            "400 Bad Request: messages must not be empty"
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+// Turn Groq rate-limit errors into a "try again in Xs" reply.
+//
+// groqai only hands us the error text, so the wait comes from Groq's message
+// ("... Please try again in 7.66s.") rather than the rate-limit headers. The remaining
+// quota is only reported in those headers, so it isn't logged.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::time::Duration;
+use tracing::warn;
+
+static RETRY_IN_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)try again in\s+([0-9.hms]+)").unwrap());
+
+// One "<number><unit>" part of a Go-style duration such as "1m30.5s".
+static DURATION_PART_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\d+(?:\.\d+)?)(ms|h|m|s)").unwrap());
+
+pub fn is_rate_limit_error(err: &str) -> bool {
+    let err = err.to_lowercase();
+    err.contains("429") || err.contains("rate limit") || err.contains("rate_limit")
+}
+
+// Wait suggested by a rate-limit error, if it names one.
+pub fn rate_limit_retry_after(err: &str) -> Option<Duration> {
+    if !is_rate_limit_error(err) {
+        return None;
+    }
+    let caps = RETRY_IN_RE.captures(err)?;
+
+    let total: f64 = DURATION_PART_RE
+        .captures_iter(&caps[1])
+        .map(|part| {
+            let amount: f64 = part[1].parse().unwrap_or(0.0);
+            match &part[2] {
+                "ms" => amount / 1000.0,
+                "h" => amount * 3600.0,
+                "m" => amount * 60.0,
+                _ => amount,
+            }
+        })
+        .sum();
+    (total > 0.0).then(|| Duration::from_secs_f64(total))
+}
+
+// User-facing text for a failed model call.
+pub fn model_error_message(err: &str) -> String {
+    if !is_rate_limit_error(err) {
+        return format!("Error: {err}.");
+    }
+
+    warn!("Model rate limited: {err}");
+    match rate_limit_retry_after(err) {
+        // Round up so the user never retries a moment too early.
+        Some(wait) => format!(
+            "Rate limited, try again in {}s.",
+            wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
+        ),
+        None => "Rate limited, try again in a moment.".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{model_error_message, rate_limit_retry_after};
+    use std::time::Duration;
+
+    #[test]
+    fn reads_wait_from_groq_message() {
+        let err = "429 Too Many Requests: Rate limit reached for model `openai/gpt-oss-120b` \
+                   on tokens per minute (TPM). Please try again in 7.66s.";
+        assert_eq!(
+            rate_limit_retry_after(err),
+            Some(Duration::from_secs_f64(7.66))
+        );
+        assert_eq!(model_error_message(err), "Rate limited, try again in 8s.");
+
+        let err = "rate_limit_exceeded: Please try again in 1m30.5s.";
+        assert_eq!(
+            rate_limit_retry_after(err),
+            Some(Duration::from_secs_f64(90.5))
+        );
+        let err = "Rate limit reached. Please try again in 250ms.";
+        assert_eq!(model_error_message(err), "Rate limited, try again in 1s.");
+    }
+
+    #[test]
+    fn other_errors_keep_their_text() {
+        assert_eq!(
+            model_error_message("model call timed out after 60s"),
+            "Error: model call timed out after 60s."
+        );
+        assert_eq!(
+            model_error_message("429 Too Many Requests"),
+            "Rate limited, try again in a moment."
+        );
+    }
+}
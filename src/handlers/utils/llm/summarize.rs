@@ -0,0 +1,42 @@
+// Map step of the /search map-reduce path: summarize each chunk of a large page.
+
+use super::run_main_model;
+use groqai::GroqClient;
+use std::time::Duration;
+use tracing::{error, info};
+
+// Summarize every chunk against the user's question, skipping chunks whose call fails.
+// Returns an error only when no chunk could be summarized at all.
+pub async fn summarize_chunks(
+    groq: &GroqClient,
+    chunks: &[String],
+    question: &str,
+    model: &str,
+    system_prompt: String,
+    timeout: Duration,
+) -> Result<Vec<String>, String> {
+    let total = chunks.len();
+    let mut summaries: Vec<String> = Vec::with_capacity(total);
+
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let part = idx + 1;
+        info!("Summarizing chunk {part}/{total}");
+
+        let prompt =
+            format!("User question: {question}\n\nWebResource part {part} of {total}:\n{chunk}");
+
+        match run_main_model(groq, &prompt, model, system_prompt.clone(), timeout).await {
+            Ok(summary) if !summary.is_empty() => {
+                summaries.push(format!("[Part {part}/{total}]\n{summary}"));
+            }
+            Ok(_) => error!("Chunk {part}/{total} produced an empty summary"),
+            Err(e) => error!("Chunk {part}/{total} failed: {e}"),
+        }
+    }
+
+    if summaries.is_empty() {
+        return Err("No chunk of the web resource could be summarized".to_string());
+    }
+
+    Ok(summaries)
+}
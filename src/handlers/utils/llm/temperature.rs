@@ -0,0 +1,26 @@
+// Keep temperatures inside the range each provider accepts instead of letting the API reject them.
+
+use crate::config::Provider;
+use tracing::warn;
+
+pub fn clamp_temperature(provider: Provider, value: f32) -> f32 {
+    let (min, max) = provider.temperature_range();
+    let clamped = value.clamp(min, max);
+    if clamped != value {
+        warn!("Temperature {value} is out of range for {provider:?}, using {clamped}");
+    }
+    clamped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::clamp_temperature;
+    use crate::config::Provider;
+
+    #[test]
+    fn clamps_to_groq_range() {
+        assert_eq!(clamp_temperature(Provider::Groq, 0.7), 0.7);
+        assert_eq!(clamp_temperature(Provider::Groq, -1.0), 0.0);
+        assert_eq!(clamp_temperature(Provider::Groq, 3.5), 2.0);
+    }
+}
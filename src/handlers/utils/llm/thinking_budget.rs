@@ -0,0 +1,149 @@
+// Heuristic reasoning budget so trivial prompts don't pay for long reasoning.
+
+const GREETINGS: &[&str] = &[
+    "hi", "hello", "hey", "hola", "buenas", "buenos", "thanks", "thank", "gracias", "ok", "okay",
+    "bye", "adios", "adiós", "chao",
+];
+
+const COMPLEX_KEYWORDS: &[&str] = &[
+    "why",
+    "how",
+    "explain",
+    "compare",
+    "analyze",
+    "analyse",
+    "difference",
+    "step by step",
+    "por qué",
+    "porque",
+    "cómo",
+    "como",
+    "explica",
+    "compara",
+    "analiza",
+    "diferencia",
+];
+
+// Suggest how many reasoning tokens a prompt deserves, from 0 (greetings) up to 4096.
+pub fn suggest_thinking_budget(prompt: &str) -> i32 {
+    let text = prompt.trim().to_lowercase();
+    if text.is_empty() {
+        return 0;
+    }
+
+    // Greetings and acknowledgements need no reasoning at all.
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let is_greeting = words.first().is_some_and(|w| GREETINGS.contains(w));
+    if words.is_empty() || (words.len() <= 3 && is_greeting) {
+        return 0;
+    }
+
+    let mut score = 0;
+    let chars = text.chars().count();
+    if chars > 200 {
+        score += 1;
+    }
+    if chars > 800 {
+        score += 1;
+    }
+    if COMPLEX_KEYWORDS.iter().any(|k| has_phrase(&words, k)) {
+        score += 1;
+    }
+    if text.contains("```") || text.contains("<code>") {
+        score += 2;
+    }
+    if text.matches('?').count() > 1 {
+        score += 1;
+    }
+
+    match score {
+        0 => 512,
+        1 => 1024,
+        2 => 2048,
+        _ => 4096,
+    }
+}
+
+// Whole-word match, so "how" doesn't fire on "show" nor "como" on "cómodo".
+fn has_phrase(words: &[&str], phrase: &str) -> bool {
+    let phrase: Vec<&str> = phrase.split_whitespace().collect();
+    words.windows(phrase.len()).any(|w| w == phrase.as_slice())
+}
+
+// Groq only limits reasoning through `reasoning_effort`, so the budget is bucketed into it.
+pub fn reasoning_effort_for(budget: i32) -> &'static str {
+    match budget {
+        ..=512 => "low",
+        513..=2048 => "medium",
+        _ => "high",
+    }
+}
+
+// Only the gpt-oss models accept low/medium/high; the rest reject the field.
+pub fn supports_reasoning_effort(model: &str) -> bool {
+    model.starts_with("openai/gpt-oss")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reasoning_effort_for, suggest_thinking_budget, supports_reasoning_effort};
+
+    #[test]
+    fn greetings_get_no_budget() {
+        assert_eq!(suggest_thinking_budget("hola!"), 0);
+        assert_eq!(suggest_thinking_budget("Hi there"), 0);
+        assert_eq!(suggest_thinking_budget("   "), 0);
+    }
+
+    #[test]
+    fn short_prompts_without_question_mark_still_get_a_budget() {
+        assert_eq!(suggest_thinking_budget("explain monads"), 1024);
+        assert_eq!(suggest_thinking_budget("capital of France"), 512);
+    }
+
+    #[test]
+    fn keywords_match_whole_words_only() {
+        assert_eq!(suggest_thinking_budget("show me the list"), 512);
+        assert_eq!(suggest_thinking_budget("estoy muy cómodo aquí"), 512);
+        assert_eq!(suggest_thinking_budget("how does it work"), 1024);
+        assert_eq!(
+            suggest_thinking_budget("explícame paso a paso, por qué"),
+            1024
+        );
+        assert_eq!(suggest_thinking_budget("do it step by step"), 1024);
+    }
+
+    #[test]
+    fn simple_question_gets_small_budget() {
+        assert_eq!(
+            suggest_thinking_budget("What is the capital of France?"),
+            512
+        );
+    }
+
+    #[test]
+    fn explanation_requests_get_more() {
+        let budget = suggest_thinking_budget("Explain why the sky is blue?");
+        assert!(budget > 512, "got {budget}");
+    }
+
+    #[test]
+    fn code_blocks_get_the_highest_budget() {
+        let prompt = "How do I fix this? ```fn main() { let x: i32 = \"a\"; }```";
+        assert_eq!(suggest_thinking_budget(prompt), 4096);
+    }
+
+    #[test]
+    fn budgets_map_to_reasoning_effort() {
+        assert_eq!(reasoning_effort_for(0), "low");
+        assert_eq!(reasoning_effort_for(512), "low");
+        assert_eq!(reasoning_effort_for(1024), "medium");
+        assert_eq!(reasoning_effort_for(2048), "medium");
+        assert_eq!(reasoning_effort_for(4096), "high");
+        assert!(supports_reasoning_effort("openai/gpt-oss-120b"));
+        assert!(!supports_reasoning_effort("llama-3.3-70b-versatile"));
+    }
+}
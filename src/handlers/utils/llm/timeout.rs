@@ -0,0 +1,54 @@
+// Per-call deadline for model requests so a stalled provider can't hang a handler.
+
+use once_cell::sync::Lazy;
+use std::{
+    fmt::Display,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// When the last model call finished and whether it succeeded (shown by /status).
+static LAST_CALL: Lazy<Mutex<Option<(Instant, bool)>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn last_model_call() -> Option<(Instant, bool)> {
+    LAST_CALL.lock().ok().and_then(|last| *last)
+}
+
+// Await a model call for at most `limit`, flattening provider and timeout errors into text.
+pub async fn with_model_timeout<T, E, F>(limit: Duration, call: F) -> Result<T, String>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    let res = match tokio::time::timeout(limit, call).await {
+        Ok(res) => res.map_err(|e| e.to_string()),
+        Err(_) => Err(format!("model call timed out after {}s", limit.as_secs())),
+    };
+    if let Ok(mut last) = LAST_CALL.lock() {
+        *last = Some((Instant::now(), res.is_ok()));
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::with_model_timeout;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn returns_result_when_in_time() {
+        let res = with_model_timeout(Duration::from_secs(1), async { Ok::<_, String>(7) }).await;
+        assert_eq!(res, Ok(7));
+    }
+
+    #[tokio::test]
+    async fn reports_timeout_when_too_slow() {
+        let res = with_model_timeout(Duration::from_millis(10), async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok::<_, String>(7)
+        })
+        .await;
+        assert!(res.unwrap_err().contains("timed out"));
+    }
+}
@@ -0,0 +1,93 @@
+// Text-to-speech helper for an OpenAI-compatible `/audio/speech` endpoint.
+
+use crate::config::TtsConfig;
+use crate::handlers::utils::truncate_chars;
+use crate::http::http_client;
+use serde_json::json;
+use std::time::Duration;
+
+// Longest text sent to the speech endpoint, in chars.
+const TTS_MAX_CHARS: usize = 4000;
+
+// Synthesize `text` and return the encoded audio bytes (format from config).
+pub async fn synthesize_speech(cfg: &TtsConfig, text: &str) -> Result<Vec<u8>, String> {
+    let body = json!({
+        "model": cfg.model,
+        "voice": cfg.voice,
+        "input": truncate_chars(text, TTS_MAX_CHARS),
+        "response_format": cfg.format,
+    });
+
+    let resp = http_client()
+        .post(&cfg.url)
+        .timeout(Duration::from_secs(60))
+        .bearer_auth(&cfg.api_key)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let detail = resp.text().await.unwrap_or_default();
+        let mut err = format!("TTS request failed ({status}): {detail}");
+        if cfg.debug_requests {
+            err.push_str(&redact_api_key(
+                &format!("\nRequest: POST {} {body}", cfg.url),
+                cfg,
+            ));
+        }
+        return Err(err);
+    }
+
+    let bytes = resp.bytes().await.map_err(|e| e.to_string())?;
+    Ok(bytes.to_vec())
+}
+
+// Remove the API key from text about to be logged.
+fn redact_api_key(text: &str, cfg: &TtsConfig) -> String {
+    if cfg.api_key.is_empty() {
+        return text.to_string();
+    }
+    text.replace(&cfg.api_key, "<redacted>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::synthesize_speech;
+    use crate::config::TtsConfig;
+    use axum::{Router, http::StatusCode, routing::post};
+
+    #[tokio::test]
+    async fn debug_errors_carry_the_redacted_request() {
+        let app = Router::new().route(
+            "/audio/speech",
+            post(|| async { (StatusCode::BAD_REQUEST, "unknown voice") }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let mut cfg = TtsConfig {
+            enabled: true,
+            url: format!("http://{addr}/audio/speech"),
+            api_key: "sk-test-key".into(),
+            model: "playai-tts".into(),
+            voice: "Nobody".into(),
+            format: "mp3".into(),
+            debug_requests: false,
+        };
+        // The key shows up in the input so a leak would be visible.
+        let text = "read sk-test-key";
+
+        let err = synthesize_speech(&cfg, text).await.unwrap_err();
+        assert!(err.contains("400") && err.contains("unknown voice"));
+        assert!(!err.contains("Request:"));
+
+        cfg.debug_requests = true;
+        let err = synthesize_speech(&cfg, text).await.unwrap_err();
+        assert!(err.contains("Request: POST"));
+        assert!(err.contains(r#""voice":"Nobody""#));
+        assert!(!err.contains("sk-test-key"));
+    }
+}
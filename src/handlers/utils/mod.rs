@@ -4,6 +4,12 @@ pub use chat_action_keep_alive::ChatActionKeepAlive;
 pub mod escape_telegram_code_entities;
 pub use escape_telegram_code_entities::escape_telegram_code_entities;
 
+pub mod html_to_plain_text;
+pub use html_to_plain_text::html_to_plain_text;
+
+pub mod answer_filters;
+pub use answer_filters::{AnswerFilter, AnswerPipeline};
+
 pub mod format_messages_xml;
 pub use format_messages_xml::format_messages_xml;
 
@@ -13,7 +19,79 @@ pub use extract_user_info::extract_user_info;
 pub mod llm;
 
 pub mod fetch_simplified_body;
-pub use fetch_simplified_body::fetch_simplified_body;
+pub use fetch_simplified_body::{
+    FetchedPage, PageMetadata, SimplifiedPage, fetch_page, fetch_simplified_body,
+};
+
+pub mod scrape_error;
+pub use scrape_error::ScrapeError;
+
+pub mod scrape_limiter;
+pub use scrape_limiter::{ScrapeLimiter, init_scrape_limiter, scrape_limiter};
+
+pub mod scrape_geo;
+pub use scrape_geo::ScrapeGeo;
+
+pub mod scrape_page;
+pub use scrape_page::scrape_page;
+
+pub mod scrape_pages;
+pub use scrape_pages::{ScrapedPages, scrape_pages};
+
+pub mod pipeline_stages;
+pub use pipeline_stages::{PipelineStage, StageTimer};
+
+pub mod split_into_chunks;
+pub use split_into_chunks::split_into_chunks;
+
+pub mod format_age;
+pub use format_age::{format_age, with_age};
+
+pub mod prune_history;
+pub use prune_history::prune_history;
+
+pub mod forget_edited_turn;
+pub use forget_edited_turn::forget_edited_turn;
+
+pub mod chat_policy;
+pub use chat_policy::{ChatScope, command_refusal};
+
+pub mod chat_admin;
+pub use chat_admin::sender_is_chat_admin;
+
+pub mod chat_settings;
+pub use chat_settings::{
+    ask_memory_enabled, disabled_commands, set_ask_memory, set_command_disabled, settings_chat_id,
+};
+
+pub mod reset_generation;
+pub use reset_generation::{ResetGuard, mark_history_reset};
+
+pub mod truncate_chars;
+pub use truncate_chars::{truncate_chars, truncate_for_storage, truncation_indicator};
+
+pub mod thread_fallback;
+pub use thread_fallback::{is_thread_permission_error, warn_thread_fallback};
+
+pub mod active_commands;
+pub use active_commands::{ActiveCommand, notify_active_commands};
+
+pub mod seen_updates;
+pub use seen_updates::first_delivery;
+
+pub mod edited_replies;
+pub use edited_replies::{has_recent_replies, remember_replies, take_replies};
+
+pub mod answer_placeholder;
+pub use answer_placeholder::{fill_placeholder, send_placeholder, take_placeholder};
 
 pub mod send_reply_or_plain;
 pub use send_reply_or_plain::send_reply_or_plain;
+
+pub mod send_long_reply;
+pub use send_long_reply::{
+    TELEGRAM_MAX_MESSAGE_LEN, send_long_reply, split_telegram_message, telegram_len,
+};
+
+pub mod send_document_reply;
+pub use send_document_reply::{send_answer, send_document_reply};
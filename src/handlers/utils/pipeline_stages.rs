@@ -0,0 +1,109 @@
+// Per-stage timing of a command pipeline, logged as one line when the command ends
+// (also on early returns), so operators can see where the time goes.
+
+use std::time::{Duration, Instant};
+use tracing::info;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PipelineStage {
+    Validate,
+    FetchHistory,
+    Scrape,
+    ModelCall,
+    Format,
+    Send,
+    Persist,
+}
+
+impl PipelineStage {
+    pub fn name(self) -> &'static str {
+        match self {
+            PipelineStage::Validate => "validate",
+            PipelineStage::FetchHistory => "fetch_history",
+            PipelineStage::Scrape => "scrape",
+            PipelineStage::ModelCall => "model_call",
+            PipelineStage::Format => "format",
+            PipelineStage::Send => "send",
+            PipelineStage::Persist => "persist",
+        }
+    }
+}
+
+pub struct StageTimer {
+    command: &'static str,
+    started: Instant,
+    current: Option<(PipelineStage, Instant)>,
+    // Time per stage in first-entered order; a stage entered twice adds up.
+    stages: Vec<(PipelineStage, Duration)>,
+}
+
+impl StageTimer {
+    pub fn start(command: &'static str) -> Self {
+        Self {
+            command,
+            started: Instant::now(),
+            current: None,
+            stages: Vec::new(),
+        }
+    }
+
+    // Close the running stage, if any, and start timing `stage`.
+    pub fn enter(&mut self, stage: PipelineStage) {
+        self.close_current();
+        self.current = Some((stage, Instant::now()));
+    }
+
+    fn close_current(&mut self) {
+        let Some((stage, since)) = self.current.take() else {
+            return;
+        };
+        let spent = since.elapsed();
+        match self.stages.iter_mut().find(|(s, _)| *s == stage) {
+            Some((_, total)) => *total += spent,
+            None => self.stages.push((stage, spent)),
+        }
+    }
+
+    // "validate=3ms scrape=850ms ...": the stages run so far, in order.
+    pub fn summary(&self) -> String {
+        self.stages
+            .iter()
+            .map(|(stage, spent)| format!("{}={}ms", stage.name(), spent.as_millis()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl Drop for StageTimer {
+    fn drop(&mut self) {
+        self.close_current();
+        info!(
+            "{} pipeline: {} total={}ms",
+            self.command,
+            self.summary(),
+            self.started.elapsed().as_millis()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PipelineStage, StageTimer};
+    use std::time::Duration;
+
+    #[test]
+    fn records_stages_in_order_and_adds_up_repeats() {
+        let mut timer = StageTimer::start("search");
+        timer.enter(PipelineStage::Validate);
+        timer.enter(PipelineStage::Scrape);
+        std::thread::sleep(Duration::from_millis(15));
+        timer.enter(PipelineStage::Validate);
+        timer.enter(PipelineStage::Send);
+        timer.close_current();
+
+        let names: Vec<_> = timer.stages.iter().map(|(s, _)| s.name()).collect();
+        assert_eq!(names, ["validate", "scrape", "send"]);
+        assert!(timer.stages[1].1 >= Duration::from_millis(15));
+        assert!(timer.summary().starts_with("validate="));
+    }
+}
@@ -0,0 +1,36 @@
+// Retention policy: keep only the most recent active history rows per user and chat.
+
+use sqlx::PgPool;
+
+// Soft-delete every non-cleared row beyond the `keep` most recent ones for this user/chat.
+// Returns how many rows were pruned.
+pub async fn prune_history(
+    pool: &PgPool,
+    user_id: i64,
+    chat_id: i64,
+    keep: i64,
+) -> Result<u64, sqlx::Error> {
+    let res = sqlx::query!(
+        r#"
+        UPDATE messages
+        SET deleted_at = now()
+        WHERE id IN (
+          SELECT id
+          FROM messages
+          WHERE user_telegram_id = $1
+            AND chat_telegram_id = $2
+            AND deleted_at IS NULL
+            AND is_cleared = FALSE
+          ORDER BY created_at DESC, id DESC
+          OFFSET $3
+        )
+        "#,
+        user_id,
+        chat_id,
+        keep
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(res.rows_affected())
+}
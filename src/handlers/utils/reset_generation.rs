@@ -0,0 +1,69 @@
+// Reset generations: lets a long command notice a /reset that ran while it was working.
+//
+// Semantics: a command snapshots the generation of its (user, chat) when it starts. /reset
+// bumps it after clearing. If the generation changed by the time the command would save
+// its turn, the turn is dropped, so a reset always leaves an empty history behind even
+// when an answer was still in flight. The answer itself has already been sent.
+
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, sync::Mutex};
+
+// Only (user, chat) pairs that were ever reset get an entry; missing means generation 0.
+static GENERATIONS: Lazy<Mutex<HashMap<(i64, i64), u64>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn generation(key: (i64, i64)) -> u64 {
+    GENERATIONS
+        .lock()
+        .map(|map| map.get(&key).copied().unwrap_or(0))
+        .unwrap_or(0)
+}
+
+// Record that the history of this user/chat was reset.
+pub fn mark_history_reset(user_id: i64, chat_id: i64) {
+    if let Ok(mut map) = GENERATIONS.lock() {
+        *map.entry((user_id, chat_id)).or_insert(0) += 1;
+    }
+}
+
+// Generation snapshot taken when a command starts.
+pub struct ResetGuard {
+    key: (i64, i64),
+    started_at: u64,
+}
+
+impl ResetGuard {
+    pub fn start(user_id: i64, chat_id: i64) -> Self {
+        let key = (user_id, chat_id);
+        Self {
+            key,
+            started_at: generation(key),
+        }
+    }
+
+    // True if /reset ran for this user/chat since `start`.
+    pub fn reset_since_start(&self) -> bool {
+        generation(self.key) != self.started_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResetGuard, mark_history_reset};
+
+    #[test]
+    fn reset_during_a_command_is_detected() {
+        let guard = ResetGuard::start(880000001, -880000001);
+        assert!(!guard.reset_since_start());
+
+        // /reset for another chat doesn't affect this command.
+        mark_history_reset(880000001, -880000002);
+        assert!(!guard.reset_since_start());
+
+        mark_history_reset(880000001, -880000001);
+        assert!(guard.reset_since_start());
+
+        // Commands started after the reset save normally.
+        assert!(!ResetGuard::start(880000001, -880000001).reset_since_start());
+    }
+}
@@ -0,0 +1,71 @@
+// Why a page couldn't be fetched, so /search can tell the user what went wrong.
+
+use std::error::Error as _;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ScrapeError {
+    #[error("DNS lookup failed: {0}")]
+    Dns(String),
+    #[error("TLS error: {0}")]
+    Tls(String),
+    #[error("request timed out")]
+    Timeout,
+    // Any other failure to connect or send the request.
+    #[error("connection failed: {0}")]
+    Connect(String),
+    #[error("HTTP status {0}")]
+    Status(u16),
+    #[error("could not read the body: {0}")]
+    Body(String),
+    #[error("not an HTML page: {0}")]
+    NonHtml(String),
+}
+
+impl ScrapeError {
+    // Classify a reqwest failure by walking its source chain (hyper, DNS, rustls).
+    pub fn from_reqwest(err: &reqwest::Error) -> Self {
+        let mut chain = err.to_string();
+        let mut source = err.source();
+        while let Some(inner) = source {
+            chain.push_str(": ");
+            chain.push_str(&inner.to_string());
+            source = inner.source();
+        }
+        let lower = chain.to_lowercase();
+
+        if err.is_timeout() {
+            ScrapeError::Timeout
+        } else if let Some(status) = err.status() {
+            ScrapeError::Status(status.as_u16())
+        } else if err.is_body() || err.is_decode() {
+            ScrapeError::Body(chain)
+        } else if lower.contains("dns error") || lower.contains("failed to lookup address") {
+            ScrapeError::Dns(chain)
+        } else if ["certificate", "tls", "ssl", "handshake", "corrupt message"]
+            .iter()
+            .any(|k| lower.contains(k))
+        {
+            ScrapeError::Tls(chain)
+        } else {
+            ScrapeError::Connect(chain)
+        }
+    }
+
+    // Short explanation for the chat; details stay in the logs.
+    pub fn user_message(&self) -> String {
+        match self {
+            ScrapeError::Dns(_) => {
+                "I couldn't find that site (its address doesn't resolve).".into()
+            }
+            ScrapeError::Tls(_) => {
+                "The site's certificate is invalid or its secure connection failed.".into()
+            }
+            ScrapeError::Timeout => "The page timed out.".into(),
+            ScrapeError::Connect(_) => "I couldn't connect to the site.".into(),
+            ScrapeError::Status(code) => format!("The site returned an error (got a {code})."),
+            ScrapeError::Body(_) => "The page stopped loading halfway.".into(),
+            ScrapeError::NonHtml(kind) => format!("That URL isn't a web page ({kind})."),
+        }
+    }
+}
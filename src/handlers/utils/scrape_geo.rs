@@ -0,0 +1,67 @@
+// Location scrape.do fetches a page from, for region-specific prices and content.
+
+// Countries accepted by scrape.do's `geoCode` (ISO 3166-1 alpha-2, lowercase).
+const COUNTRIES: [&str; 24] = [
+    "us", "gb", "ca", "au", "de", "fr", "es", "it", "nl", "se", "ch", "pl", "br", "mx", "ar", "cl",
+    "co", "ve", "in", "jp", "kr", "sg", "tr", "za",
+];
+
+// Regions accepted by scrape.do's `regionalGeoCode`.
+const REGIONS: [&str; 6] = [
+    "europe",
+    "asia",
+    "africa",
+    "oceania",
+    "northamerica",
+    "southamerica",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrapeGeo {
+    Country(&'static str),
+    Region(&'static str),
+}
+
+impl ScrapeGeo {
+    // A known country or region code, case-insensitive.
+    pub fn parse(code: &str) -> Option<Self> {
+        let code = code.to_ascii_lowercase();
+        if let Some(country) = COUNTRIES.iter().find(|c| **c == code) {
+            return Some(ScrapeGeo::Country(country));
+        }
+        REGIONS
+            .iter()
+            .find(|r| **r == code)
+            .map(|region| ScrapeGeo::Region(region))
+    }
+
+    // Query parameter appended to the scrape.do request.
+    pub fn query_param(&self) -> String {
+        match self {
+            ScrapeGeo::Country(code) => format!("geoCode={code}"),
+            ScrapeGeo::Region(code) => format!("regionalGeoCode={code}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScrapeGeo;
+
+    #[test]
+    fn parses_known_countries_and_regions_only() {
+        assert_eq!(ScrapeGeo::parse("US"), Some(ScrapeGeo::Country("us")));
+        assert_eq!(
+            ScrapeGeo::parse("europe"),
+            Some(ScrapeGeo::Region("europe"))
+        );
+        assert_eq!(ScrapeGeo::parse("xx"), None);
+        assert_eq!(ScrapeGeo::parse("geoCode=us"), None);
+
+        assert_eq!(ScrapeGeo::Country("us").query_param(), "geoCode=us");
+        assert_eq!(
+            ScrapeGeo::Region("asia").query_param(),
+            "regionalGeoCode=asia"
+        );
+    }
+}
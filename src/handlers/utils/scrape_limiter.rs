@@ -0,0 +1,92 @@
+// Cap on scrape.do requests in flight across all chats. Plans limit concurrency and
+// answer errors past it, so requests over the cap wait for a free slot instead.
+
+use once_cell::sync::OnceCell;
+use std::{future::Future, sync::Arc};
+use tokio::sync::Semaphore;
+
+#[derive(Clone, Debug)]
+pub struct ScrapeLimiter {
+    // None when unlimited.
+    slots: Option<Arc<Semaphore>>,
+}
+
+impl ScrapeLimiter {
+    // At most `max` runs at once; 0 means no limit.
+    pub fn new(max: usize) -> Self {
+        Self {
+            slots: (max > 0).then(|| Arc::new(Semaphore::new(max))),
+        }
+    }
+
+    // Await `fut` once a slot is free, holding the slot until it completes.
+    pub async fn run<F: Future>(&self, fut: F) -> F::Output {
+        // The semaphore is never closed, so acquiring only waits.
+        let _permit = match &self.slots {
+            Some(slots) => slots.acquire().await.ok(),
+            None => None,
+        };
+        fut.await
+    }
+}
+
+static LIMITER: OnceCell<ScrapeLimiter> = OnceCell::new();
+
+// Set the limit at startup. Has no effect once the limiter was used.
+pub fn init_scrape_limiter(max: usize) {
+    let _ = LIMITER.set(ScrapeLimiter::new(max));
+}
+
+// The shared limiter; unlimited if `init_scrape_limiter` never ran (tests).
+pub fn scrape_limiter() -> &'static ScrapeLimiter {
+    LIMITER.get_or_init(|| ScrapeLimiter::new(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScrapeLimiter;
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    // Highest number of runs seen in flight at once for `tasks` runs through `limiter`.
+    async fn peak_concurrency(limiter: ScrapeLimiter, tasks: usize) -> usize {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..tasks)
+            .map(|_| {
+                let (limiter, in_flight, peak) = (limiter.clone(), in_flight.clone(), peak.clone());
+                tokio::spawn(async move {
+                    limiter
+                        .run(async {
+                            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                            peak.fetch_max(now, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(20)).await;
+                            in_flight.fetch_sub(1, Ordering::SeqCst);
+                        })
+                        .await
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        peak.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn caps_concurrent_runs() {
+        assert_eq!(peak_concurrency(ScrapeLimiter::new(2), 6).await, 2);
+    }
+
+    #[tokio::test]
+    async fn zero_means_unlimited() {
+        assert_eq!(peak_concurrency(ScrapeLimiter::new(0), 4).await, 4);
+    }
+}
@@ -0,0 +1,141 @@
+// Fetch a page through scrape.do, falling back to a direct request when scrape.do rejects it.
+
+use super::{FetchedPage, ScrapeError, ScrapeGeo, fetch_page, scrape_limiter};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tracing::info;
+
+static JSON_OBJECT_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{[^{}]*\}").unwrap());
+
+// scrape.do request for `url`, fetched from `geo` when one is given.
+fn scrapedo_url(scrapedo_token: &str, url: &str, geo: Option<ScrapeGeo>) -> String {
+    // Encode ampersands to keep query safe.
+    let encoded = url.replace('&', "%26");
+    let mut request = format!("http://api.scrape.do/?token={scrapedo_token}&url={encoded}");
+    if let Some(geo) = geo {
+        request.push('&');
+        request.push_str(&geo.query_param());
+    }
+    request
+}
+
+// True if the host of `url` is one of `domains` or a subdomain of one.
+fn is_direct_domain(url: &str, domains: &[String]) -> bool {
+    let Some(host) = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_lowercase))
+    else {
+        return false;
+    };
+    domains.iter().any(|domain| {
+        host == *domain
+            || host
+                .strip_suffix(domain.as_str())
+                .is_some_and(|sub| sub.ends_with('.'))
+    })
+}
+
+// Returns the fetched page and which path served it ("scrape.do" or "direct").
+// The direct paths ignore `geo`: they always fetch from the bot's own location.
+pub async fn scrape_page(
+    scrapedo_token: &str,
+    url: &str,
+    geo: Option<ScrapeGeo>,
+    direct_domains: &[String],
+) -> Result<(FetchedPage, &'static str), ScrapeError> {
+    // Allowlisted sites need no rendering or anti-bot bypass: don't spend credits on them.
+    if is_direct_domain(url, direct_domains) {
+        info!("Fetching an allowlisted site directly");
+        return fetch_page(url).await.map(|page| (page, "direct"));
+    }
+
+    let encoded = url.replace('&', "%26");
+    // Waits for a free slot when the plan's concurrency is used up.
+    let fetched = match scrape_limiter()
+        .run(fetch_page(&scrapedo_url(scrapedo_token, url, geo)))
+        .await
+    {
+        Ok(fetched) => fetched,
+        // scrape.do answers 400 for URLs it refuses to handle.
+        Err(ScrapeError::Status(400)) => {
+            info!("scrape.do rejected the URL, fetching it directly");
+            return fetch_page(&encoded).await.map(|page| (page, "direct"));
+        }
+        Err(e) => return Err(e),
+    };
+
+    // scrape.do reports rejected URLs as a JSON error body.
+    let body = &fetched.page.body;
+    if JSON_OBJECT_RE.is_match(body) && body.contains(r#""StatusCode":400"#) {
+        info!("scrape.do rejected the URL, fetching it directly");
+        return fetch_page(&encoded).await.map(|page| (page, "direct"));
+    }
+
+    Ok((fetched, "scrape.do"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_direct_domain, scrape_page, scrapedo_url};
+    use crate::handlers::utils::ScrapeGeo;
+    use axum::{Router, routing::get};
+
+    #[test]
+    fn appends_the_geo_parameter() {
+        assert_eq!(
+            scrapedo_url("tok", "https://e.io/?a=1&b=2", None),
+            "http://api.scrape.do/?token=tok&url=https://e.io/?a=1%26b=2"
+        );
+        assert_eq!(
+            scrapedo_url("tok", "https://e.io", ScrapeGeo::parse("us")),
+            "http://api.scrape.do/?token=tok&url=https://e.io&geoCode=us"
+        );
+        assert_eq!(
+            scrapedo_url("tok", "https://e.io", ScrapeGeo::parse("europe")),
+            "http://api.scrape.do/?token=tok&url=https://e.io&regionalGeoCode=europe"
+        );
+    }
+
+    #[test]
+    fn matches_allowlisted_hosts_and_subdomains() {
+        let domains = vec!["wikipedia.org".to_string()];
+        assert!(is_direct_domain(
+            "https://wikipedia.org/wiki/Rust",
+            &domains
+        ));
+        assert!(is_direct_domain(
+            "https://en.Wikipedia.org/wiki/Rust",
+            &domains
+        ));
+        assert!(!is_direct_domain("https://notwikipedia.org/", &domains));
+        assert!(!is_direct_domain(
+            "https://wikipedia.org.evil.io/",
+            &domains
+        ));
+        assert!(!is_direct_domain("not a url", &domains));
+        assert!(!is_direct_domain("https://wikipedia.org/", &[]));
+    }
+
+    #[tokio::test]
+    async fn allowlisted_sites_skip_scrape_do() {
+        let app = Router::new().route(
+            "/page",
+            get(|| async {
+                (
+                    [(axum::http::header::CONTENT_TYPE, "text/html")],
+                    "<html><body><p>Direct</p></body></html>",
+                )
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let url = format!("http://{addr}/page");
+        let (fetched, via) = scrape_page("tok", &url, None, &["127.0.0.1".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(via, "direct");
+        assert!(fetched.page.body.contains("Direct"));
+    }
+}
@@ -0,0 +1,86 @@
+// Fetch several pages at once, keeping the ones that loaded so one dead link doesn't
+// fail the whole command.
+
+use super::{ScrapeError, SimplifiedPage};
+use std::future::Future;
+use tokio::task::JoinSet;
+use tracing::warn;
+
+#[derive(Debug, Default)]
+pub struct ScrapedPages {
+    // (url, page) for every URL that loaded, in the order they were given.
+    pub pages: Vec<(String, SimplifiedPage)>,
+    // URLs that couldn't be fetched and why, in the order they were given.
+    pub failed: Vec<(String, ScrapeError)>,
+}
+
+// Fetch every URL concurrently with `fetch` and split the results into pages and failures.
+pub async fn scrape_pages<F, Fut>(urls: &[String], fetch: F) -> ScrapedPages
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<SimplifiedPage, ScrapeError>> + Send + 'static,
+{
+    let mut set = JoinSet::new();
+    for (idx, url) in urls.iter().enumerate() {
+        let fut = fetch(url.clone());
+        set.spawn(async move { (idx, fut.await) });
+    }
+
+    let mut results: Vec<Option<Result<SimplifiedPage, ScrapeError>>> = vec![None; urls.len()];
+    while let Some(joined) = set.join_next().await {
+        match joined {
+            Ok((idx, res)) => results[idx] = Some(res),
+            Err(e) => warn!("Page fetch task failed: {e}"),
+        }
+    }
+
+    let mut scraped = ScrapedPages::default();
+    for (url, res) in urls.iter().zip(results) {
+        match res {
+            Some(Ok(page)) => scraped.pages.push((url.clone(), page)),
+            Some(Err(e)) => {
+                warn!("Couldn't fetch {url}: {e}");
+                scraped.failed.push((url.clone(), e));
+            }
+            None => scraped.failed.push((
+                url.clone(),
+                ScrapeError::Connect("fetch task failed".into()),
+            )),
+        }
+    }
+    scraped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scrape_pages;
+    use crate::handlers::utils::{PageMetadata, ScrapeError, SimplifiedPage};
+
+    #[tokio::test]
+    async fn keeps_pages_that_loaded_and_lists_failures() {
+        let urls = vec![
+            "https://dead.example".to_string(),
+            "https://ok.example".to_string(),
+        ];
+
+        let scraped = scrape_pages(&urls, |url| async move {
+            if url.contains("dead") {
+                Err(ScrapeError::Status(404))
+            } else {
+                Ok(SimplifiedPage {
+                    body: format!("body of {url}"),
+                    metadata: PageMetadata::default(),
+                })
+            }
+        })
+        .await;
+
+        assert_eq!(
+            scraped.failed,
+            vec![("https://dead.example".to_string(), ScrapeError::Status(404))]
+        );
+        assert_eq!(scraped.pages.len(), 1);
+        assert_eq!(scraped.pages[0].0, "https://ok.example");
+        assert_eq!(scraped.pages[0].1.body, "body of https://ok.example");
+    }
+}
@@ -0,0 +1,54 @@
+// Update ids handled recently, so a webhook delivery Telegram retries (it got no timely
+// 200) isn't processed twice. Entries older than the window are dropped on every check.
+
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use teloxide::types::UpdateId;
+
+static SEEN: Lazy<Mutex<HashMap<UpdateId, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Record `id` and return true the first time it shows up within `window`; a zero window
+// disables the check.
+pub fn first_delivery(id: UpdateId, window: Duration) -> bool {
+    if window.is_zero() {
+        return true;
+    }
+    let Ok(mut seen) = SEEN.lock() else {
+        return true;
+    };
+    seen.retain(|_, at| at.elapsed() <= window);
+    seen.insert(id, Instant::now()).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::first_delivery;
+    use std::time::Duration;
+    use teloxide::types::UpdateId;
+
+    #[test]
+    fn repeated_update_ids_are_ignored_within_the_window() {
+        let window = Duration::from_secs(60);
+
+        assert!(first_delivery(UpdateId(990001), window));
+        assert!(!first_delivery(UpdateId(990001), window));
+        assert!(first_delivery(UpdateId(990002), window));
+
+        // Disabled: every delivery goes through.
+        assert!(first_delivery(UpdateId(990003), Duration::ZERO));
+        assert!(first_delivery(UpdateId(990003), Duration::ZERO));
+    }
+
+    #[test]
+    fn ids_are_accepted_again_after_the_window() {
+        let window = Duration::from_millis(10);
+
+        assert!(first_delivery(UpdateId(990004), window));
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(first_delivery(UpdateId(990004), window));
+    }
+}
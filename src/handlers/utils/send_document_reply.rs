@@ -0,0 +1,134 @@
+// Sends text as a downloadable file, for answers too long to read comfortably in chat.
+
+use super::{
+    TELEGRAM_MAX_MESSAGE_LEN, html_to_plain_text, is_thread_permission_error, remember_replies,
+    send_long_reply, split_telegram_message, take_placeholder, take_replies, telegram_len,
+    truncate_chars, truncation_indicator, warn_thread_fallback,
+};
+use crate::config::AnswerConfig;
+use std::time::Duration;
+use teloxide::{
+    ApiError, RequestError,
+    prelude::*,
+    types::{InputFile, MessageId, ParseMode, ReplyParameters},
+};
+use tracing::warn;
+
+// Characters of the answer quoted in the caption next to the file.
+const CAPTION_PREVIEW_CHARS: usize = 200;
+
+// Send `content` as a file named `file_name`, replying to `msg` in groups and topics.
+pub async fn send_document_reply(
+    bot: &Bot,
+    msg: &Message,
+    content: String,
+    file_name: &str,
+    caption: String,
+) -> Result<Message, teloxide::RequestError> {
+    let chat_id = msg.chat.id;
+    let thread_id = msg.thread_id;
+    // The fallback without the thread is not a reply either: the replied-to message lives
+    // in the topic, so Telegram would put the file there again.
+    let build = |with_thread: bool| {
+        let file = InputFile::memory(content.clone().into_bytes()).file_name(file_name.to_string());
+        let mut req = bot.send_document(chat_id, file).caption(caption.clone());
+        if msg.chat.title().is_some() && with_thread {
+            req = req.reply_parameters(ReplyParameters::new(msg.id).allow_sending_without_reply());
+            if let Some(tid) = thread_id {
+                req = req.message_thread_id(tid);
+            }
+        }
+        req
+    };
+
+    match build(true).await {
+        Err(e) if thread_id.is_some() && is_thread_permission_error(&e) => {
+            warn_thread_fallback(chat_id);
+            build(false).await
+        }
+        res => res,
+    }
+}
+
+// Send an HTML answer in chat, or as a plain-text file with a short preview when it is
+// longer than the configured threshold. For an edited command the previous answer is
+// edited in place when the new one fits a single message, and replaced otherwise; a
+// pending "…" placeholder is handled the same way.
+pub async fn send_answer(
+    bot: &Bot,
+    msg: &Message,
+    html: String,
+    cfg: &AnswerConfig,
+) -> Result<(), teloxide::RequestError> {
+    let chat_id = msg.chat.id;
+    let window = Duration::from_secs(cfg.edit_window_secs);
+    let placeholder = take_placeholder(chat_id, msg.id);
+    let edited = match msg.edit_date() {
+        Some(_) => take_replies(chat_id, msg.id, window).unwrap_or_default(),
+        None => Vec::new(),
+    };
+    let previous: Vec<MessageId> = placeholder.into_iter().chain(edited).collect();
+
+    let sent: Result<Vec<MessageId>, _> =
+        if cfg.file_threshold == 0 || telegram_len(&html) <= cfg.file_threshold {
+            let single = split_telegram_message(&html, TELEGRAM_MAX_MESSAGE_LEN, true).len() <= 1;
+            match previous.first() {
+                Some(&first) if single && edit_reply(bot, chat_id, first, &html).await => {
+                    delete_replies(bot, chat_id, &previous[1..]).await;
+                    remember_replies(chat_id, msg.id, vec![first], window);
+                    return Ok(());
+                }
+                _ => send_long_reply(bot, msg, html, true)
+                    .await
+                    .map(|sent| sent.iter().map(|m| m.id).collect()),
+            }
+        } else {
+            let text = html_to_plain_text(&html);
+            let preview = truncate_chars(&text, CAPTION_PREVIEW_CHARS);
+            // The caption is sent without a parse mode, so the label goes in as plain text.
+            let caption = format!(
+                "{} {}",
+                preview.trim_end(),
+                truncation_indicator(&cfg.truncation_label, None)
+            );
+            send_document_reply(bot, msg, text, "answer.txt", caption)
+                .await
+                .map(|m| vec![m.id])
+        };
+    let sent = match sent {
+        Ok(sent) => sent,
+        Err(e) => {
+            // Callers may retry with a fresh send; don't leave the placeholder hanging.
+            delete_replies(bot, chat_id, placeholder.as_slice()).await;
+            return Err(e);
+        }
+    };
+
+    // The new answer is out, the outdated one can go.
+    delete_replies(bot, chat_id, &previous).await;
+    remember_replies(chat_id, msg.id, sent, window);
+    Ok(())
+}
+
+// Replace the text of an earlier reply; false if Telegram refused (e.g. it was a file).
+async fn edit_reply(bot: &Bot, chat_id: ChatId, reply_id: MessageId, html: &str) -> bool {
+    match bot
+        .edit_message_text(chat_id, reply_id, html)
+        .parse_mode(ParseMode::Html)
+        .await
+    {
+        Ok(_) | Err(RequestError::Api(ApiError::MessageNotModified)) => true,
+        Err(e) => {
+            warn!("Editing reply {reply_id} failed, sending a new one: {e}");
+            false
+        }
+    }
+}
+
+async fn delete_replies(bot: &Bot, chat_id: ChatId, replies: &[MessageId]) {
+    for &id in replies {
+        if let Err(e) = bot.delete_message(chat_id, id).await {
+            warn!("Deleting outdated reply {id} failed: {e}");
+        }
+    }
+}
@@ -0,0 +1,255 @@
+// Sends answers longer than Telegram's limit as several messages chained as replies.
+
+use super::send_reply_or_plain;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use teloxide::{
+    prelude::*,
+    types::{ParseMode, ReplyParameters},
+};
+
+// Maximum message length accepted by Telegram, in UTF-16 code units.
+pub const TELEGRAM_MAX_MESSAGE_LEN: usize = 4096;
+
+// Length as Telegram counts it: emoji and other non-BMP characters take two units.
+pub fn telegram_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+static TAG_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"<(/?)([A-Za-z][A-Za-z0-9-]*)(?:[^"'<>]|"[^"]*"|'[^']*')*>"#).unwrap()
+});
+
+// Send `text` as a reply, splitting it into parts when it exceeds the message limit.
+// In groups and forum topics every part after the first replies to the previous part,
+// so a long answer reads as one chain inside the same thread.
+pub async fn send_long_reply(
+    bot: &Bot,
+    msg: &Message,
+    text: impl Into<String>,
+    parse_html: bool,
+) -> Result<Vec<Message>, teloxide::RequestError> {
+    let text = text.into();
+    let mut parts = split_telegram_message(&text, TELEGRAM_MAX_MESSAGE_LEN, parse_html).into_iter();
+
+    // Nothing to split: let Telegram report the empty text as usual.
+    let first = parts.next().unwrap_or(text);
+    let mut previous = send_reply_or_plain(bot, msg, first, false, parse_html, None).await?;
+    let mut sent = vec![previous.clone()];
+
+    for part in parts {
+        previous = send_chained_part(bot, msg, &previous, part, parse_html).await?;
+        sent.push(previous.clone());
+    }
+
+    Ok(sent)
+}
+
+// Send one follow-up part, replying to the previous part when the chat is group-like.
+async fn send_chained_part(
+    bot: &Bot,
+    msg: &Message,
+    previous: &Message,
+    part: String,
+    parse_html: bool,
+) -> Result<Message, teloxide::RequestError> {
+    let mut req = bot.send_message(msg.chat.id, part);
+    if parse_html {
+        req = req.parse_mode(ParseMode::Html);
+    }
+
+    if msg.chat.title().is_some() {
+        // If our previous part was deleted meanwhile, still deliver the rest.
+        req = req.reply_parameters(ReplyParameters::new(previous.id).allow_sending_without_reply());
+        // Follow the previous part's topic, which is the general one after a thread fallback.
+        if let Some(tid) = previous.thread_id {
+            req = req.message_thread_id(tid);
+        }
+    }
+
+    req.await
+}
+
+// Split a message into parts of at most `max_len` UTF-16 units, preferring line boundaries.
+// With `html` set, tags are never cut and tags left open at the end of a part are
+// closed there and reopened at the start of the next one.
+pub fn split_telegram_message(text: &str, max_len: usize, html: bool) -> Vec<String> {
+    let mut parts: Vec<String> = Vec::new();
+    if max_len == 0 {
+        return parts;
+    }
+
+    let mut current = String::new();
+    let mut current_len = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_len = telegram_len(line);
+        if current_len + line_len <= max_len {
+            current.push_str(line);
+            current_len += line_len;
+            continue;
+        }
+
+        if !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+        }
+
+        // A single line above the limit has to be cut inside.
+        let mut rest = line;
+        while telegram_len(rest) > max_len {
+            let end = cut_index(rest, max_len, html);
+            parts.push(rest[..end].to_string());
+            rest = &rest[end..];
+        }
+        current.push_str(rest);
+        current_len = telegram_len(rest);
+    }
+    parts.push(current);
+
+    let parts: Vec<String> = parts
+        .into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    if html {
+        balance_html_tags(parts)
+    } else {
+        parts
+    }
+}
+
+// Byte index where a too-long line is cut: the last whitespace outside a tag in the
+// second half of the window, else the window end moved back out of any tag or entity.
+fn cut_index(line: &str, max_units: usize, html: bool) -> usize {
+    // First char that would push the window past `max_units` UTF-16 units.
+    let mut units = 0;
+    let hard_end = line
+        .char_indices()
+        .find(|(_, c)| {
+            units += c.len_utf16();
+            units > max_units
+        })
+        .map(|(idx, _)| idx)
+        .unwrap_or(line.len())
+        // Always make progress, even if one char is wider than the limit.
+        .max(line.chars().next().map_or(0, char::len_utf8));
+    let window = &line[..hard_end];
+
+    let mut end = hard_end;
+    if html {
+        if let Some(lt) = window.rfind('<')
+            && !window[lt..].contains('>')
+        {
+            end = lt;
+        }
+        if let Some(amp) = window[..end].rfind('&')
+            && !window[amp..end].contains(';')
+            && end - amp <= 10
+        {
+            end = amp;
+        }
+    }
+
+    let whitespace = window[..end]
+        .char_indices()
+        .rev()
+        .take_while(|(idx, _)| *idx >= hard_end / 2)
+        .find(|(idx, c)| c.is_whitespace() && !(html && inside_tag(&window[..*idx])))
+        .map(|(idx, _)| idx);
+
+    match whitespace {
+        Some(idx) if idx > 0 => idx,
+        _ if end > 0 => end,
+        _ => hard_end,
+    }
+}
+
+// True when `prefix` ends inside an unterminated `<...` tag.
+fn inside_tag(prefix: &str) -> bool {
+    match (prefix.rfind('<'), prefix.rfind('>')) {
+        (Some(lt), Some(gt)) => lt > gt,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+// Close tags still open at the end of each part and reopen them in the next part.
+fn balance_html_tags(parts: Vec<String>) -> Vec<String> {
+    let mut open: Vec<(String, String)> = Vec::new();
+    let mut balanced = Vec::with_capacity(parts.len());
+
+    for part in parts {
+        let mut out: String = open.iter().map(|(_, tag)| tag.as_str()).collect();
+        out.push_str(&part);
+
+        for caps in TAG_RE.captures_iter(&part) {
+            let name = caps[2].to_ascii_lowercase();
+            if caps[1].is_empty() {
+                open.push((name, caps[0].to_string()));
+            } else if let Some(pos) = open.iter().rposition(|(n, _)| *n == name) {
+                open.remove(pos);
+            }
+        }
+
+        for (name, _) in open.iter().rev() {
+            out.push_str(&format!("</{name}>"));
+        }
+        balanced.push(out);
+    }
+
+    balanced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_telegram_message, telegram_len};
+
+    #[test]
+    fn short_message_is_not_split() {
+        assert_eq!(split_telegram_message("hello", 10, false), vec!["hello"]);
+    }
+
+    #[test]
+    fn splits_on_line_boundaries() {
+        let parts = split_telegram_message("line one\nline two\nline three", 18, false);
+        assert_eq!(parts, vec!["line one\nline two", "line three"]);
+    }
+
+    #[test]
+    fn long_line_is_cut_on_whitespace() {
+        let parts = split_telegram_message("aaaa bbbb cccc", 10, false);
+        assert_eq!(parts, vec!["aaaa bbbb", "cccc"]);
+    }
+
+    #[test]
+    fn html_tags_are_reopened_in_next_part() {
+        let parts = split_telegram_message("<b>first line\nsecond line</b>", 16, true);
+        assert_eq!(parts, vec!["<b>first line</b>", "<b>second line</b>"]);
+    }
+
+    #[test]
+    fn emoji_count_as_two_units() {
+        assert_eq!(telegram_len("😀"), 2);
+        let parts = split_telegram_message(&"😀".repeat(5), 4, false);
+        assert_eq!(parts, vec!["😀😀", "😀😀", "😀"]);
+        assert!(parts.iter().all(|p| telegram_len(p) <= 4));
+    }
+
+    #[test]
+    fn cjk_text_is_split_within_limit() {
+        let text = "漢字かな交じり文".repeat(3);
+        let parts = split_telegram_message(&text, 10, false);
+        assert!(parts.iter().all(|p| telegram_len(p) <= 10));
+        assert_eq!(parts.concat(), text);
+    }
+
+    #[test]
+    fn html_cut_never_splits_a_tag_or_entity() {
+        let text = "xxxxxxxx<a href=\"https://e.io\">link</a> &amp;";
+        for part in split_telegram_message(text, 26, true) {
+            assert_eq!(part.matches('<').count(), part.matches('>').count());
+            assert!(!part.ends_with('&'));
+        }
+    }
+}
@@ -1,10 +1,13 @@
 // Sends a reply to a message, handling thread and HTML parsing options
 
+use super::{fill_placeholder, is_thread_permission_error, take_placeholder, warn_thread_fallback};
+use std::time::Duration;
 use teloxide::{
     prelude::*,
     requests::Requester,
     types::{ParseMode, ReplyParameters, ThreadId},
 };
+use tracing::warn;
 
 pub async fn send_reply_or_plain(
     bot: &Bot,
@@ -12,10 +15,48 @@ pub async fn send_reply_or_plain(
     text: impl Into<String>,
     allow_sending_without_reply: bool,
     parse_html: bool,
+    auto_delete_after: Option<Duration>,
+) -> Result<Message, teloxide::RequestError> {
+    let text: String = text.into();
+    // A pending "…" placeholder turns into this reply instead of leaving it behind.
+    let filled = match take_placeholder(msg.chat.id, msg.id) {
+        Some(placeholder) => {
+            fill_placeholder(bot, msg.chat.id, placeholder, &text, parse_html).await
+        }
+        None => None,
+    };
+    let sent = match filled {
+        Some(edited) => edited,
+        None => send_reply(bot, msg, text, allow_sending_without_reply, parse_html).await?,
+    };
+
+    // Transient replies (errors) are removed later so they don't clutter the chat.
+    if let Some(delay) = auto_delete_after {
+        let bot = bot.clone();
+        let (chat_id, message_id) = (sent.chat.id, sent.id);
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            // Fails without delete rights or once the message is gone; nothing else to do.
+            if let Err(e) = bot.delete_message(chat_id, message_id).await {
+                warn!("Could not auto-delete message {message_id} in chat {chat_id}: {e}");
+            }
+        });
+    }
+
+    Ok(sent)
+}
+
+async fn send_reply(
+    bot: &Bot,
+    msg: &Message,
+    text: impl Into<String>,
+    allow_sending_without_reply: bool,
+    parse_html: bool,
 ) -> Result<Message, teloxide::RequestError> {
     // Extract chat and optional thread identifiers
     let chat_id = msg.chat.id;
     let thread_id: Option<ThreadId> = msg.thread_id;
+    let text: String = text.into();
 
     // Determine if the chat behaves like a group (has a title)
     let is_group_like = msg.chat.title().is_some();
@@ -30,7 +71,7 @@ pub async fn send_reply_or_plain(
 
         // Start building the message request with reply parameters
         let mut req = bot
-            .send_message(chat_id, text.into())
+            .send_message(chat_id, text.clone())
             .reply_parameters(params);
         // Apply HTML parse mode if requested
         if parse_html {
@@ -43,11 +84,23 @@ pub async fn send_reply_or_plain(
             req
         };
 
-        // Send the request
-        req.await
+        // Send the request, retrying in the general topic if the thread is off-limits
+        match req.await {
+            Err(e) if thread_id.is_some() && is_thread_permission_error(&e) => {
+                warn_thread_fallback(chat_id);
+                // Not a reply either: the replied-to message lives in the topic, so Telegram
+                // would put the retry there and fail the same way.
+                let mut retry = bot.send_message(chat_id, text);
+                if parse_html {
+                    retry = retry.parse_mode(ParseMode::Html);
+                }
+                retry.await
+            }
+            res => res,
+        }
     } else {
         // Build a simple message request without reply parameters
-        let mut req = bot.send_message(chat_id, text.into());
+        let mut req = bot.send_message(chat_id, text);
         // Apply HTML parse mode if requested
         if parse_html {
             req = req.parse_mode(ParseMode::Html);
@@ -56,3 +109,65 @@ pub async fn send_reply_or_plain(
         req.await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::send_reply_or_plain;
+    use axum::{Json, Router};
+    use serde_json::{Value, json};
+    use std::sync::{Arc, Mutex};
+    use teloxide::{Bot, types::Message};
+
+    const FORUM_ID: i64 = -1005555555555;
+
+    #[tokio::test]
+    async fn closed_topic_falls_back_to_a_plain_message() {
+        // Mock Bot API: requests into a topic fail as closed, the rest succeed.
+        let requests: Arc<Mutex<Vec<Value>>> = Arc::default();
+        let recorded = requests.clone();
+        let app = Router::new().fallback(move |Json(body): Json<Value>| {
+            let recorded = recorded.clone();
+            async move {
+                let in_topic = body.get("message_thread_id").is_some();
+                recorded.lock().unwrap().push(body);
+                Json(if in_topic {
+                    json!({"ok": false, "error_code": 400, "description": "Bad Request: TOPIC_CLOSED"})
+                } else {
+                    json!({"ok": true, "result": {
+                        "message_id": 2,
+                        "date": 1_700_000_000,
+                        "chat": {"id": FORUM_ID, "type": "supergroup", "title": "Forum"},
+                        "text": "answer",
+                    }})
+                })
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let bot = Bot::new("tok").set_api_url(format!("http://{addr}/").parse().unwrap());
+        let msg: Message = serde_json::from_value(json!({
+            "message_id": 1,
+            "message_thread_id": 5,
+            "is_topic_message": true,
+            "date": 1_700_000_000,
+            "chat": {"id": FORUM_ID, "type": "supergroup", "title": "Forum", "is_forum": true},
+            "from": {"id": 42, "is_bot": false, "first_name": "Ana"},
+            "text": "/ask hi",
+        }))
+        .unwrap();
+
+        let sent = send_reply_or_plain(&bot, &msg, "answer", false, false, None)
+            .await
+            .unwrap();
+        assert_eq!(sent.id.0, 2);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["message_thread_id"], 5);
+        assert!(requests[0].get("reply_parameters").is_some());
+        assert!(requests[1].get("message_thread_id").is_none());
+        assert!(requests[1].get("reply_parameters").is_none());
+    }
+}
@@ -0,0 +1,69 @@
+// Splits long text into chunks of at most `max_chars` characters, preferring whitespace boundaries.
+
+pub fn split_into_chunks(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks: Vec<String> = Vec::new();
+    if max_chars == 0 {
+        return chunks;
+    }
+
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        // Byte index of the first char past the limit; None means the rest fits.
+        let hard_end = match rest.char_indices().nth(max_chars) {
+            Some((idx, _)) => idx,
+            None => {
+                chunks.push(rest.to_string());
+                break;
+            }
+        };
+
+        // Cut at the last whitespace of the window unless it would leave a tiny chunk.
+        let end = match rest[..hard_end].rfind(char::is_whitespace) {
+            Some(idx) if idx > 0 && idx >= hard_end / 2 => idx,
+            _ => hard_end,
+        };
+
+        let chunk = rest[..end].trim();
+        if !chunk.is_empty() {
+            chunks.push(chunk.to_string());
+        }
+        rest = rest[end..].trim_start();
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_into_chunks;
+
+    #[test]
+    fn short_text_is_a_single_chunk() {
+        assert_eq!(split_into_chunks("hello world", 100), vec!["hello world"]);
+    }
+
+    #[test]
+    fn splits_on_whitespace_within_limit() {
+        let chunks = split_into_chunks("aaaa bbbb cccc dddd", 10);
+        assert_eq!(chunks, vec!["aaaa bbbb", "cccc dddd"]);
+        assert!(chunks.iter().all(|c| c.chars().count() <= 10));
+    }
+
+    #[test]
+    fn hard_splits_words_longer_than_limit() {
+        let chunks = split_into_chunks("abcdefghij", 4);
+        assert_eq!(chunks, vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn never_splits_inside_multibyte_chars() {
+        let chunks = split_into_chunks("ñañañañaña", 3);
+        assert_eq!(chunks.concat(), "ñañañañaña");
+        assert!(chunks.iter().all(|c| c.chars().count() <= 3));
+    }
+
+    #[test]
+    fn zero_limit_yields_nothing() {
+        assert!(split_into_chunks("text", 0).is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+// Detects forum-topic errors so senders can fall back to the chat's general topic.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use teloxide::{ApiError, RequestError, types::ChatId};
+use tracing::{debug, warn};
+
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+// True when Telegram rejected a request because of the `message_thread_id`
+// (missing topic rights, closed/deleted topic or unknown thread).
+pub fn is_thread_permission_error(err: &RequestError) -> bool {
+    let RequestError::Api(ApiError::Unknown(text)) = err else {
+        return false;
+    };
+
+    let text = text.to_lowercase();
+    text.contains("message thread not found")
+        || text.contains("topic_closed")
+        || text.contains("topic_deleted")
+        || (text.contains("not enough rights") && text.contains("topic"))
+}
+
+// Log the thread fallback once per process at warn level, then only at debug level.
+pub fn warn_thread_fallback(chat_id: ChatId) {
+    if !WARNED.swap(true, Ordering::Relaxed) {
+        warn!(
+            "Can't post in the forum topic of chat_id={chat_id}; falling back to the general topic. Check the bot's topic permissions."
+        );
+    } else {
+        debug!("Thread fallback used for chat_id={chat_id}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_thread_permission_error;
+    use teloxide::{ApiError, RequestError};
+
+    fn api_error(text: &str) -> RequestError {
+        RequestError::Api(ApiError::Unknown(text.to_string()))
+    }
+
+    #[test]
+    fn detects_thread_permission_errors() {
+        assert!(is_thread_permission_error(&api_error(
+            "Bad Request: message thread not found"
+        )));
+        assert!(is_thread_permission_error(&api_error(
+            "Bad Request: TOPIC_CLOSED"
+        )));
+        assert!(is_thread_permission_error(&api_error(
+            "Bad Request: not enough rights to send messages to the topic"
+        )));
+    }
+
+    #[test]
+    fn ignores_other_errors() {
+        assert!(!is_thread_permission_error(&api_error(
+            "Bad Request: can't parse entities"
+        )));
+        assert!(!is_thread_permission_error(&RequestError::Api(
+            ApiError::BotBlocked
+        )));
+    }
+}
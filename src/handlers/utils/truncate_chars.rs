@@ -0,0 +1,85 @@
+// Truncation that always cuts on a char boundary, so UTF-8 text is never corrupted.
+
+use teloxide::types::ParseMode;
+use tracing::warn;
+
+// Return the longest prefix of `s` holding at most `max_chars` characters.
+pub fn truncate_chars(s: &str, max_chars: usize) -> &str {
+    match s.char_indices().nth(max_chars) {
+        Some((idx, _)) => &s[..idx],
+        None => s,
+    }
+}
+
+// Truncate a value before saving it to the history, logging when anything was dropped.
+pub fn truncate_for_storage(value: &str, max_chars: usize, field: &str) -> String {
+    let kept = truncate_chars(value, max_chars);
+    if kept.len() < value.len() {
+        warn!(
+            "Truncated {field} for storage: {} -> {max_chars} chars",
+            value.chars().count()
+        );
+    }
+    kept.to_string()
+}
+
+// Render a "text was cut" label so it is literal text in the given parse mode
+// (None means plain text), instead of markup that breaks the message.
+#[allow(deprecated)]
+pub fn truncation_indicator(label: &str, mode: Option<ParseMode>) -> String {
+    let special: &[char] = match mode {
+        Some(ParseMode::Html) => return html_escape::encode_text(label).into_owned(),
+        Some(ParseMode::MarkdownV2) => &[
+            '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.',
+            '!', '\\',
+        ],
+        Some(ParseMode::Markdown) => &['_', '*', '`', '['],
+        None => return label.to_string(),
+    };
+
+    let mut out = String::with_capacity(label.len() * 2);
+    for c in label.chars() {
+        if special.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{truncate_chars, truncation_indicator};
+    use teloxide::types::ParseMode;
+
+    #[test]
+    fn indicator_is_escaped_per_parse_mode() {
+        let label = "…(truncated) <more> & co.";
+        assert_eq!(
+            truncation_indicator(label, Some(ParseMode::Html)),
+            "…(truncated) &lt;more&gt; &amp; co."
+        );
+        assert_eq!(
+            truncation_indicator(label, Some(ParseMode::MarkdownV2)),
+            "…\\(truncated\\) <more\\> & co\\."
+        );
+        assert_eq!(truncation_indicator(label, None), label);
+    }
+
+    #[test]
+    fn keeps_short_strings_untouched() {
+        assert_eq!(truncate_chars("hola", 10), "hola");
+        assert_eq!(truncate_chars("hola", 4), "hola");
+    }
+
+    #[test]
+    fn cuts_on_char_boundary_with_multibyte_text() {
+        assert_eq!(truncate_chars("añoñú", 3), "año");
+        assert_eq!(truncate_chars("😀😀😀", 2), "😀😀");
+    }
+
+    #[test]
+    fn zero_limit_yields_empty() {
+        assert_eq!(truncate_chars("abc", 0), "");
+    }
+}
@@ -0,0 +1,132 @@
+// Greets groups when the bot is added and members when they join.
+
+use crate::{commands::Command, config::AppConfig, handlers::utils::send_reply_or_plain};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use teloxide::{
+    RequestError,
+    prelude::*,
+    types::{ChatMemberUpdated, User},
+    utils::command::BotCommands,
+};
+use tracing::{error, info, warn};
+
+static PLACEHOLDER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{(name|chat|commands)\}").unwrap());
+
+// Fill the {name}, {chat} and {commands} placeholders of the welcome template in one pass,
+// so placeholders inside the filled values (a user named "{chat}") stay as typed.
+pub fn render_welcome(template: &str, name: &str, chat: &str, commands: &str) -> String {
+    PLACEHOLDER_RE
+        .replace_all(template, |caps: &Captures| match &caps[1] {
+            "name" => name,
+            "chat" => chat,
+            _ => commands,
+        })
+        .into_owned()
+}
+
+// Greet human members listed in a `new_chat_members` service message.
+pub async fn welcome_new_members(
+    bot: Bot,
+    msg: Message,
+    app_config: AppConfig,
+) -> ResponseResult<()> {
+    if !app_config.welcome.enabled {
+        return Ok(());
+    }
+
+    let names: Vec<String> = msg
+        .new_chat_members()
+        .unwrap_or_default()
+        .iter()
+        .filter(|u| !u.is_bot)
+        .map(User::full_name)
+        .collect();
+
+    // Only bots joined (the bot itself is greeted through my_chat_member).
+    if names.is_empty() {
+        return Ok(());
+    }
+
+    let text = render_welcome(
+        &app_config.welcome.message,
+        &names.join(", "),
+        msg.chat.title().unwrap_or("this chat"),
+        &Command::descriptions().to_string(),
+    );
+
+    if let Err(e) = send_reply_or_plain(&bot, &msg, text, true, false, None).await {
+        log_send_failure(e);
+    }
+
+    Ok(())
+}
+
+// Greet a group right after the bot itself is added to it.
+pub async fn welcome_on_bot_added(
+    bot: Bot,
+    update: ChatMemberUpdated,
+    app_config: AppConfig,
+) -> ResponseResult<()> {
+    let added = !update.old_chat_member.is_present() && update.new_chat_member.is_present();
+    let is_group = update.chat.is_group() || update.chat.is_supergroup();
+    if !app_config.welcome.enabled || !added || !is_group {
+        return Ok(());
+    }
+
+    info!("Bot added to chat_id={}", update.chat.id);
+
+    let chat_title = update.chat.title().unwrap_or("this chat");
+    let text = render_welcome(
+        &app_config.welcome.message,
+        chat_title,
+        chat_title,
+        &Command::descriptions().to_string(),
+    );
+
+    if let Err(e) = bot.send_message(update.chat.id, text).await {
+        log_send_failure(e);
+    }
+
+    Ok(())
+}
+
+// A missing permission to post is expected in some groups, so it is only a warning.
+fn log_send_failure(e: RequestError) {
+    match e {
+        RequestError::Api(api) => warn!("Welcome message not sent (can't post here?): {api}"),
+        other => error!("Welcome message failed: {other}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_welcome;
+
+    #[test]
+    fn fills_all_placeholders() {
+        let out = render_welcome(
+            "Hi {name}, welcome to {chat}!\n{commands}",
+            "Ana",
+            "Rust",
+            "/help",
+        );
+        assert_eq!(out, "Hi Ana, welcome to Rust!\n/help");
+    }
+
+    #[test]
+    fn template_without_placeholders_is_kept() {
+        assert_eq!(render_welcome("Hello!", "Ana", "Rust", "/help"), "Hello!");
+    }
+
+    #[test]
+    fn placeholders_in_names_are_not_expanded() {
+        let out = render_welcome(
+            "Hi {name}, welcome to {chat}!\n{commands}",
+            "{chat} {commands}",
+            "Rust {name}",
+            "/help",
+        );
+        assert_eq!(out, "Hi {chat} {commands}, welcome to Rust {name}!\n/help");
+    }
+}
@@ -0,0 +1,135 @@
+// Per-dependency health report served at /health/detail: each check runs concurrently and
+// reports whether it passed, how long it took and why it failed.
+
+use crate::{config::AppConfig, http::http_client};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use std::time::{Duration, Instant};
+
+const GROQ_MODELS_URL: &str = "https://api.groq.com/openai/v1/models";
+// Account details; answering it costs no scrape.do credits.
+const SCRAPEDO_INFO_URL: &str = "https://q.scrape.do:8000/info";
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outside services probed by /health/detail, besides the database.
+#[derive(Clone)]
+pub struct UpstreamChecks {
+    pub groq_models_url: String,
+    pub groq_api_key: String,
+    pub scrapedo_info_url: String,
+    pub scrapedo_token: String,
+}
+
+impl UpstreamChecks {
+    pub fn from_config(cfg: &AppConfig) -> Self {
+        Self {
+            groq_models_url: GROQ_MODELS_URL.to_string(),
+            groq_api_key: cfg.groq_api_key.clone(),
+            scrapedo_info_url: SCRAPEDO_INFO_URL.to_string(),
+            scrapedo_token: cfg.scrapedo_token.clone(),
+        }
+    }
+}
+
+/// Outcome of one dependency check.
+#[derive(Debug)]
+pub struct DependencyStatus {
+    pub name: &'static str,
+    pub ok: bool,
+    pub latency_ms: u128,
+    pub error: Option<String>,
+}
+
+impl DependencyStatus {
+    fn to_json(&self) -> Value {
+        let mut entry = json!({
+            "name": self.name,
+            "ok": self.ok,
+            "latency_ms": self.latency_ms as u64,
+        });
+        if let Some(error) = &self.error {
+            entry["error"] = json!(error);
+        }
+        entry
+    }
+}
+
+async fn timed<F>(name: &'static str, check: F) -> DependencyStatus
+where
+    F: Future<Output = Result<(), String>>,
+{
+    let started = Instant::now();
+    let result = check.await;
+    DependencyStatus {
+        name,
+        ok: result.is_ok(),
+        latency_ms: started.elapsed().as_millis(),
+        error: result.err(),
+    }
+}
+
+async fn check_database(pool: Option<&PgPool>) -> Result<(), String> {
+    let pool = pool.ok_or("database is not configured")?;
+    sqlx::query("SELECT 1")
+        .execute(pool)
+        .await
+        .map(|_| ())
+        .map_err(|_| "database is unreachable".to_string())
+}
+
+// Any 2xx answer counts; the status code is reported otherwise (never the body, it may
+// echo credentials).
+async fn check_http(req: reqwest::RequestBuilder) -> Result<(), String> {
+    let resp = req.timeout(CHECK_TIMEOUT).send().await.map_err(|e| {
+        if e.is_timeout() {
+            "timed out"
+        } else {
+            "unreachable"
+        }
+        .to_string()
+    })?;
+    match resp.status() {
+        s if s.is_success() => Ok(()),
+        s => Err(format!("HTTP {}", s.as_u16())),
+    }
+}
+
+/// Run every check; upstreams are skipped when `upstreams` is None (tests, no config).
+pub async fn check_dependencies(
+    pool: Option<&PgPool>,
+    upstreams: Option<&UpstreamChecks>,
+) -> Vec<DependencyStatus> {
+    let database = timed("database", check_database(pool));
+    let Some(up) = upstreams else {
+        return vec![database.await];
+    };
+
+    let groq = timed(
+        "groq",
+        check_http(
+            http_client()
+                .get(&up.groq_models_url)
+                .bearer_auth(&up.groq_api_key),
+        ),
+    );
+    let scrapedo = timed(
+        "scrape.do",
+        check_http(
+            http_client()
+                .get(&up.scrapedo_info_url)
+                .query(&[("token", &up.scrapedo_token)]),
+        ),
+    );
+
+    let (database, groq, scrapedo) = tokio::join!(database, groq, scrapedo);
+    vec![database, groq, scrapedo]
+}
+
+/// JSON body of the report, with "ok" overall only when every dependency passed.
+pub fn health_report(statuses: &[DependencyStatus]) -> Value {
+    let healthy = statuses.iter().all(|s| s.ok);
+    json!({
+        "status": if healthy { "ok" } else { "degraded" },
+        "dependencies": statuses.iter().map(DependencyStatus::to_json).collect::<Vec<_>>(),
+    })
+}
@@ -0,0 +1,29 @@
+// Shared HTTP client for the bot's own outgoing requests (web pages, Telegram files,
+// speech, keep-alive), so connections are pooled instead of rebuilt on every call.
+
+use crate::config::HttpConfig;
+use once_cell::sync::OnceCell;
+use std::time::Duration;
+
+static CLIENT: OnceCell<reqwest::Client> = OnceCell::new();
+
+/// Build a client with the connection pool settings of `cfg`.
+pub fn build_http_client(cfg: &HttpConfig) -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .pool_idle_timeout(Duration::from_secs(cfg.pool_idle_timeout_secs))
+        .pool_max_idle_per_host(cfg.pool_max_idle_per_host)
+        .build()
+}
+
+/// Configure the shared client at startup. Has no effect once it was used.
+pub fn init_http_client(cfg: &HttpConfig) -> reqwest::Result<()> {
+    let client = build_http_client(cfg)?;
+    let _ = CLIENT.set(client);
+    Ok(())
+}
+
+/// The shared client; uses the default pool settings if `init_http_client` never ran (tests).
+/// Per-request deadlines are set with `RequestBuilder::timeout`.
+pub fn http_client() -> &'static reqwest::Client {
+    CLIENT.get_or_init(|| build_http_client(&HttpConfig::default()).unwrap_or_default())
+}
@@ -0,0 +1,71 @@
+// Periodic self-ping so free-tier hosts don't put an idle webhook service to sleep.
+
+use crate::http::http_client;
+use std::time::Duration;
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+use tracing::{debug, warn};
+
+/// GET `url` every `interval` until the returned task is aborted.
+/// Failures are only logged: a missed ping must never take the bot down.
+pub fn spawn_keepalive(url: url::Url, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick fires immediately; the service is obviously awake at startup.
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            let ping = http_client()
+                .get(url.clone())
+                .timeout(Duration::from_secs(10))
+                .send()
+                .await;
+            match ping {
+                Ok(resp) if resp.status().is_success() => debug!("Keep-alive ping ok"),
+                Ok(resp) => warn!("Keep-alive ping returned {}", resp.status()),
+                Err(e) => warn!("Keep-alive ping failed: {e}"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::spawn_keepalive;
+    use axum::{Router, routing::get};
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn pings_until_aborted() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = Router::new().route(
+            "/health",
+            get(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                "ok"
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let url = url::Url::parse(&format!("http://{addr}/health")).unwrap();
+        let task = spawn_keepalive(url, Duration::from_millis(50));
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        task.abort();
+
+        let seen = hits.load(Ordering::SeqCst);
+        assert!(seen >= 2, "expected several pings, got {seen}");
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(hits.load(Ordering::SeqCst), seen);
+    }
+}
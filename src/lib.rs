@@ -1,6 +1,9 @@
 pub mod commands;
 pub mod config;
 pub mod handlers;
+pub mod health;
+pub mod http;
+pub mod keepalive;
 pub mod prompts;
 pub mod server;
 pub mod trace;
@@ -22,8 +25,12 @@ use tokio::signal;
 use trace::init_tracing;
 use tracing::{error, info};
 
+// Longest a shutdown waits for the restart notices to go out.
+const SHUTDOWN_NOTICE_LIMIT: Duration = Duration::from_secs(5);
+
 pub async fn run() -> Result<(), BoxError> {
     init_tracing();
+    handlers::mark_started();
 
     let cfg = match AppConfig::from_env() {
         Ok(c) => c,
@@ -37,6 +44,23 @@ pub async fn run() -> Result<(), BoxError> {
 
     let bot = Bot::new(cfg.token.clone());
 
+    // Commands are matched against this username, so `/ask@otherbot` is left alone; the
+    // dispatcher fetches it again, this only fails fast on a bad token.
+    match bot.get_me().await {
+        Ok(me) => info!("Logged in as @{}", me.username()),
+        Err(e) => {
+            error!("The bot's username could not be fetched");
+            return Err(Box::new(e) as BoxError);
+        }
+    }
+
+    handlers::utils::init_scrape_limiter(cfg.scrapedo_max_concurrency);
+
+    if let Err(e) = http::init_http_client(&cfg.http) {
+        error!("The HTTP client could not be built");
+        return Err(Box::new(e) as BoxError);
+    }
+
     let groq = match GroqClient::with_api_key(cfg.clone().groq_api_key) {
         Ok(client) => client,
         Err(e) => {
@@ -63,13 +87,32 @@ pub async fn run() -> Result<(), BoxError> {
     };
 
     let handler = get_update_handler();
-    let mut dispatcher = Dispatcher::builder(bot.clone(), handler)
-        .dependencies(dptree::deps![pool.clone(), groq.clone(), cfg.clone()])
-        .enable_ctrlc_handler()
-        .build();
+    let mut builder = Dispatcher::builder(bot.clone(), handler).dependencies(dptree::deps![
+        pool.clone(),
+        groq.clone(),
+        cfg.clone()
+    ]);
+    // Polling mode handles the signals itself (below), so the restart notices go out first.
+    if cfg.hosting {
+        builder = builder.enable_ctrlc_handler();
+    }
+    let mut dispatcher = builder.build();
 
     if !cfg.hosting {
         info!("Running in polling mode (local development).");
+        let token = dispatcher.shutdown_token();
+        let notice_bot = bot.clone();
+        let shutdown_notice = cfg.shutdown_notice;
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            info!("Shutdown signal received (SIGINT/SIGTERM). Stopping dispatcher.");
+            if shutdown_notice {
+                handlers::utils::notify_active_commands(&notice_bot, SHUTDOWN_NOTICE_LIMIT).await;
+            }
+            if let Ok(stopped) = token.shutdown() {
+                stopped.await;
+            }
+        });
         info!("Bot started");
         dispatcher.dispatch().await;
         info!("Dispatcher exited (polling mode).");
@@ -101,40 +144,25 @@ pub async fn run() -> Result<(), BoxError> {
     info!("Webhook configured");
     info!("Bot started");
 
-    let app = server::build_router(Some(webhook_router));
+    let app = server::build_router(
+        Some(webhook_router),
+        Some(pool.clone()),
+        Some(health::UpstreamChecks::from_config(&cfg)),
+    );
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
     let server = axum::serve(listener, app);
 
-    let shutdown_signal = async {
-        let ctrl = signal::ctrl_c();
-        #[cfg(unix)]
-        {
-            let mut term_stream =
-                match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
-                    Ok(s) => s,
-                    Err(err) => {
-                        error!("Failed to register SIGTERM handler: {}", err);
-                        ctrl.await.expect("ctrl_c failed");
-                        return;
-                    }
-                };
-
-            tokio::select! {
-                _ = ctrl => {},
-                _ = term_stream.recv() => {},
-            }
-        }
-        #[cfg(not(unix))]
-        {
-            ctrl.await.expect("ctrl_c failed");
-        }
-    };
-
-    let server_with_shutdown = server.with_graceful_shutdown(async {
+    let notice_bot = bot.clone();
+    let shutdown_notice = cfg.shutdown_notice;
+    let server_with_shutdown = server.with_graceful_shutdown(async move {
         tokio::select! {
-            _ = shutdown_signal => {
+            _ = shutdown_signal() => {
                 info!("Shutdown signal received (SIGINT/SIGTERM). Stopping listener & server.");
+                if shutdown_notice {
+                    handlers::utils::notify_active_commands(&notice_bot, SHUTDOWN_NOTICE_LIMIT)
+                        .await;
+                }
             }
             _ = stop_future => {
                 info!("Listener stop_future resolved.");
@@ -148,10 +176,22 @@ pub async fn run() -> Result<(), BoxError> {
         }
     });
 
+    let keepalive_handle = cfg.keepalive_url.clone().map(|url| {
+        info!(
+            "Keep-alive ping to {} every {}s",
+            url, cfg.keepalive_interval_secs
+        );
+        keepalive::spawn_keepalive(url, Duration::from_secs(cfg.keepalive_interval_secs))
+    });
+
     dispatcher
         .dispatch_with_listener(update_listener, LoggingErrorHandler::new())
         .await;
 
+    if let Some(handle) = keepalive_handle {
+        handle.abort();
+    }
+
     if let Err(e) = server_handle.await {
         error!("Server task join error: {}", e);
     }
@@ -159,3 +199,29 @@ pub async fn run() -> Result<(), BoxError> {
     info!("Bot shutdown complete.");
     Ok(())
 }
+
+// Resolves on SIGINT, or SIGTERM on unix.
+async fn shutdown_signal() {
+    let ctrl = signal::ctrl_c();
+    #[cfg(unix)]
+    {
+        let mut term_stream =
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(s) => s,
+                Err(err) => {
+                    error!("Failed to register SIGTERM handler: {}", err);
+                    ctrl.await.expect("ctrl_c failed");
+                    return;
+                }
+            };
+
+        tokio::select! {
+            _ = ctrl => {},
+            _ = term_stream.recv() => {},
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        ctrl.await.expect("ctrl_c failed");
+    }
+}
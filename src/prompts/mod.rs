@@ -1,2 +1,5 @@
 mod types;
 pub use types::{AiPrompt, Prompt};
+
+mod system;
+pub use system::{PromptOverrides, build_system_prompt};
@@ -0,0 +1,76 @@
+// System instruction builder shared by every handler that asks a model for an answer.
+
+use crate::config::AnswerConfig;
+
+// Per-answer additions to the base system prompt.
+#[derive(Clone, Debug, Default)]
+pub struct PromptOverrides {
+    // Operator instructions added to every answer (ANSWER_SYSTEM_PROMPT).
+    pub custom: Option<String>,
+    // A footer is appended after the model answers, so it must not write its own.
+    pub has_footer: bool,
+}
+
+impl PromptOverrides {
+    pub fn from_config(cfg: &AnswerConfig) -> Self {
+        Self {
+            custom: cfg.system_prompt.clone(),
+            has_footer: cfg.footer.is_some(),
+        }
+    }
+}
+
+// Append the target language, custom instructions and footer directive to `base`.
+pub fn build_system_prompt(base: &str, user_lang: &str, overrides: &PromptOverrides) -> String {
+    let mut prompt = base.trim_end().to_string();
+
+    prompt.push_str(&format!(
+        "\n\nMain lang is \"{user_lang}\": answer in it unless the user explicitly asks for another language."
+    ));
+
+    if let Some(custom) = overrides.custom.as_deref().map(str::trim)
+        && !custom.is_empty()
+    {
+        prompt.push_str("\n\nAdditional instructions:\n");
+        prompt.push_str(custom);
+    }
+
+    if overrides.has_footer {
+        prompt.push_str(
+            "\n\nA footer is added to your answer automatically: don't end with a signature or sign-off of your own.",
+        );
+    }
+
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PromptOverrides, build_system_prompt};
+
+    #[test]
+    fn injects_language_only_by_default() {
+        let prompt = build_system_prompt("Base prompt.\n", "pt-BR", &PromptOverrides::default());
+        assert!(prompt.starts_with("Base prompt.\n\nMain lang is \"pt-BR\""));
+        assert!(!prompt.contains("Additional instructions"));
+        assert!(!prompt.contains("footer"));
+    }
+
+    #[test]
+    fn injects_custom_prompt_and_footer_directive() {
+        let overrides = PromptOverrides {
+            custom: Some("  Be brief.  ".into()),
+            has_footer: true,
+        };
+        let prompt = build_system_prompt("Base", "es", &overrides);
+        assert!(prompt.contains("Main lang is \"es\""));
+        assert!(prompt.contains("Additional instructions:\nBe brief."));
+        assert!(prompt.ends_with("sign-off of your own."));
+
+        let blank = PromptOverrides {
+            custom: Some("   ".into()),
+            has_footer: false,
+        };
+        assert!(!build_system_prompt("Base", "es", &blank).contains("Additional"));
+    }
+}
@@ -7,6 +7,8 @@ pub struct AiPrompt {
     pub preprocess: String,
     pub web_search: String,
     pub vision: String,
+    pub chunk_summary: String,
+    pub search_not_found: String,
 }
 
 pub enum Prompt {
@@ -16,6 +18,8 @@ pub enum Prompt {
     Preprocess,
     WebSearch,
     Vision,
+    ChunkSummary,
+    SearchNotFound,
 }
 
 impl AiPrompt {
@@ -27,6 +31,8 @@ impl AiPrompt {
             preprocess: include_str!("./prompts/preprocess.md").to_string(),
             web_search: include_str!("./prompts/web_search.md").to_string(),
             vision: include_str!("./prompts/vision.md").to_string(),
+            chunk_summary: include_str!("./prompts/chunk_summary.md").to_string(),
+            search_not_found: include_str!("./prompts/search_not_found.md").to_string(),
         }
     }
 
@@ -38,6 +44,8 @@ impl AiPrompt {
             Prompt::Preprocess => self.preprocess.clone(),
             Prompt::WebSearch => self.web_search.clone(),
             Prompt::Vision => self.vision.clone(),
+            Prompt::ChunkSummary => self.chunk_summary.clone(),
+            Prompt::SearchNotFound => self.search_not_found.clone(),
         }
     }
 }
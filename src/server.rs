@@ -1,16 +1,115 @@
-use axum::{Router, routing::get};
+use crate::health::{UpstreamChecks, check_dependencies, health_report};
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
 use serde_json::json;
+use sqlx::PgPool;
+use tracing::warn;
+
+/// Shared by the routes: the database for /ready, upstreams for /health/detail.
+#[derive(Clone, Default)]
+pub struct ServerState {
+    pub pool: Option<PgPool>,
+    pub upstreams: Option<UpstreamChecks>,
+}
+
+/// Error returned by the HTTP routes, rendered as `{"error", "message"}` JSON.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    error: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, error: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            error,
+            message: message.into(),
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    pub fn unavailable(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::SERVICE_UNAVAILABLE, "unavailable", message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = Json(json!({ "error": self.error, "message": self.message }));
+        (self.status, body).into_response()
+    }
+}
 
 pub async fn health_handler() -> axum::Json<serde_json::Value> {
     axum::Json(json!({ "status": "ok" }))
 }
 
+/// Readiness: the bot can serve updates only while the database answers.
+pub async fn ready_handler(
+    State(state): State<ServerState>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let Some(pool) = state.pool else {
+        return Err(ApiError::unavailable("database is not configured"));
+    };
+
+    match sqlx::query("SELECT 1").execute(&pool).await {
+        Ok(_) => Ok(Json(json!({ "status": "ready" }))),
+        Err(e) => {
+            warn!("Readiness check failed: {e}");
+            Err(ApiError::unavailable("database is unreachable"))
+        }
+    }
+}
+
+/// Every dependency with its latency and error; 503 as soon as one of them fails.
+pub async fn health_detail_handler(State(state): State<ServerState>) -> Response {
+    let statuses = check_dependencies(state.pool.as_ref(), state.upstreams.as_ref()).await;
+    for failed in statuses.iter().filter(|s| !s.ok) {
+        warn!(
+            "Health check of {} failed: {}",
+            failed.name,
+            failed.error.as_deref().unwrap_or_default()
+        );
+    }
+    let code = if statuses.iter().all(|s| s.ok) {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (code, Json(health_report(&statuses))).into_response()
+}
+
+async fn not_found_handler() -> ApiError {
+    ApiError::not_found("no such route")
+}
+
 /// Build the main router. webhook_router can be passed (via webhooks::axumtorouter)
-/// or None to only use the /health route (useful in tests).
-pub fn build_router(webhook_router: Option<Router>) -> Router {
-    let base = Router::new().route("/health", get(health_handler));
-    match webhook_router {
+/// or None to only use the /health and /ready routes (useful in tests).
+/// /ready reports 503 when `pool` is None or the database can't be reached;
+/// /health/detail only checks the database when `upstreams` is None.
+pub fn build_router(
+    webhook_router: Option<Router>,
+    pool: Option<PgPool>,
+    upstreams: Option<UpstreamChecks>,
+) -> Router {
+    let base = Router::new()
+        .route("/health", get(health_handler))
+        .route("/health/detail", get(health_detail_handler))
+        .route("/ready", get(ready_handler))
+        .with_state(ServerState { pool, upstreams });
+    let app = match webhook_router {
         Some(r) => base.merge(r),
         None => base,
-    }
+    };
+    app.fallback(not_found_handler)
 }
@@ -0,0 +1,199 @@
+// Property tests for escape_telegram_code_entities: random mixes of tags, entities and
+// special characters must come out as HTML that Telegram's parser accepts.
+
+use proptest::prelude::*;
+use tscrapingbot_rs::handlers::utils::escape_telegram_code_entities;
+
+const TAGS: [&str; 4] = ["b", "i", "u", "s"];
+const ENTITIES: [&str; 6] = ["&amp;", "&lt;", "&gt;", "&quot;", "&#65;", "&#x41;"];
+
+#[derive(Debug, Clone)]
+enum Segment {
+    // Plain text without letters, so it can never spell a tag by accident.
+    Text(String),
+    Entity(&'static str),
+    Wrap(&'static str, Vec<Segment>),
+    Code(Vec<CodePart>),
+}
+
+#[derive(Debug, Clone)]
+enum CodePart {
+    // Anything goes inside code, including tag-like text.
+    Text(String),
+    Entity(&'static str),
+}
+
+fn decode_entity(ent: &str) -> &str {
+    match ent {
+        "&amp;" => "&",
+        "&lt;" => "<",
+        "&gt;" => ">",
+        "&quot;" => "\"",
+        numeric => numeric,
+    }
+}
+
+impl Segment {
+    fn html(&self, out: &mut String) {
+        match self {
+            Segment::Text(t) => out.push_str(t),
+            Segment::Entity(e) => out.push_str(e),
+            Segment::Wrap(tag, children) => {
+                out.push_str(&format!("<{tag}>"));
+                children.iter().for_each(|c| c.html(out));
+                out.push_str(&format!("</{tag}>"));
+            }
+            Segment::Code(parts) => {
+                out.push_str("<code>");
+                for part in parts {
+                    match part {
+                        CodePart::Text(t) => out.push_str(t),
+                        CodePart::Entity(e) => out.push_str(e),
+                    }
+                }
+                out.push_str("</code>");
+            }
+        }
+    }
+
+    // Text a reader sees once Telegram renders the segment.
+    fn plain(&self, out: &mut String) {
+        match self {
+            Segment::Text(t) => out.push_str(t),
+            Segment::Entity(e) => out.push_str(decode_entity(e)),
+            Segment::Wrap(_, children) => children.iter().for_each(|c| c.plain(out)),
+            Segment::Code(parts) => {
+                for part in parts {
+                    match part {
+                        CodePart::Text(t) => out.push_str(t),
+                        CodePart::Entity(e) => out.push_str(decode_entity(e)),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn code_part() -> impl Strategy<Value = CodePart> {
+    prop_oneof![
+        "[a-z0-9 <>&#/\"'=]{0,10}"
+            .prop_filter("must not close the block", |s| !s.contains("</code"))
+            .prop_map(CodePart::Text),
+        prop::sample::select(&ENTITIES[..]).prop_map(CodePart::Entity),
+    ]
+}
+
+fn segment() -> impl Strategy<Value = Segment> {
+    let leaf = prop_oneof![
+        "[0-9 .;#=\"'<>&]{0,12}".prop_map(Segment::Text),
+        prop::sample::select(&ENTITIES[..]).prop_map(Segment::Entity),
+        prop::collection::vec(code_part(), 0..4).prop_map(Segment::Code),
+    ];
+    leaf.prop_recursive(3, 24, 4, |inner| {
+        (
+            prop::sample::select(&TAGS[..]),
+            prop::collection::vec(inner, 0..4),
+        )
+            .prop_map(|(tag, children)| Segment::Wrap(tag, children))
+    })
+}
+
+fn is_numeric_entity(name: &str) -> bool {
+    match name.strip_prefix("#x") {
+        Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => name
+            .strip_prefix('#')
+            .is_some_and(|dec| !dec.is_empty() && dec.chars().all(|c| c.is_ascii_digit())),
+    }
+}
+
+// Parse `html` the way Telegram does (supported tags, balanced, no raw `<`, `>` or `&`,
+// nothing but text inside <code>) and return the rendered text.
+fn parse_telegram_html(html: &str) -> Result<String, String> {
+    let mut plain = String::new();
+    let mut open: Vec<&str> = Vec::new();
+    let mut rest = html;
+
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '<' => {
+                let end = rest
+                    .find('>')
+                    .ok_or_else(|| format!("raw '<' in {html:?}"))?;
+                let tag = &rest[1..end];
+                if let Some(name) = tag.strip_prefix('/') {
+                    if open.pop() != Some(name) {
+                        return Err(format!("unbalanced </{name}> in {html:?}"));
+                    }
+                } else if open.last() == Some(&"code") {
+                    return Err(format!("tag <{tag}> inside <code> in {html:?}"));
+                } else if tag == "code" || TAGS.contains(&tag) {
+                    open.push(tag);
+                } else {
+                    return Err(format!("unsupported tag <{tag}> in {html:?}"));
+                }
+                rest = &rest[end + 1..];
+            }
+            '>' => return Err(format!("raw '>' in {html:?}")),
+            '&' => {
+                let end = rest
+                    .find(';')
+                    .ok_or_else(|| format!("raw '&' in {html:?}"))?;
+                let name = &rest[1..end];
+                match name {
+                    "amp" | "lt" | "gt" | "quot" => plain.push_str(decode_entity(&rest[..=end])),
+                    n if is_numeric_entity(n) => plain.push_str(&rest[..=end]),
+                    _ => return Err(format!("raw '&' in {html:?}")),
+                }
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    if let Some(tag) = open.pop() {
+        return Err(format!("unclosed <{tag}> in {html:?}"));
+    }
+    Ok(plain)
+}
+
+#[test]
+fn parser_rejects_what_telegram_rejects() {
+    assert!(parse_telegram_html("<b>x</b> &amp; &#65;").is_ok());
+    assert!(parse_telegram_html("1 < 2").is_err());
+    assert!(parse_telegram_html("a > b").is_err());
+    assert!(parse_telegram_html("a & b").is_err());
+    assert!(parse_telegram_html("<b>x</i>").is_err());
+    assert!(parse_telegram_html("<code><b>x</b></code>").is_err());
+    assert!(parse_telegram_html("<em>x</em>").is_err());
+}
+
+proptest! {
+    #[test]
+    fn escaped_output_is_valid_telegram_html(segments in prop::collection::vec(segment(), 0..8)) {
+        let mut input = String::new();
+        let mut expected = String::new();
+        for s in &segments {
+            s.html(&mut input);
+            s.plain(&mut expected);
+        }
+
+        let out = escape_telegram_code_entities(&input);
+        let rendered = parse_telegram_html(&out);
+        prop_assert!(rendered.is_ok(), "{}", rendered.unwrap_err());
+        // Escaping changes the markup, never the text the reader sees.
+        prop_assert_eq!(rendered.unwrap(), expected);
+    }
+
+    #[test]
+    fn escaping_is_idempotent_on_its_output(segments in prop::collection::vec(segment(), 0..8)) {
+        let mut input = String::new();
+        segments.iter().for_each(|s| s.html(&mut input));
+
+        let once = escape_telegram_code_entities(&input);
+        prop_assert_eq!(escape_telegram_code_entities(&once), once);
+    }
+}
@@ -0,0 +1,85 @@
+use serial_test::serial;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use std::env;
+use tscrapingbot_rs::handlers::utils::prune_history;
+
+const USER_ID: i64 = 990_000_001;
+const CHAT_ID: i64 = -990_000_001;
+
+async fn connect() -> PgPool {
+    dotenvy::dotenv().ok();
+
+    let database_url =
+        env::var("DATABASE_URL").expect("DATABASE_URL not set (check .env or environment)");
+    PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&database_url)
+        .await
+        .expect("The database could not be connected")
+}
+
+async fn active_rows(pool: &PgPool) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT content FROM messages
+         WHERE user_telegram_id = $1 AND chat_telegram_id = $2 AND deleted_at IS NULL
+         ORDER BY id",
+    )
+    .bind(USER_ID)
+    .bind(CHAT_ID)
+    .fetch_all(pool)
+    .await
+    .expect("select failed")
+}
+
+#[tokio::test]
+#[serial]
+async fn prune_history_keeps_only_newest_rows() {
+    let pool = connect().await;
+
+    // Creates the user/chat rows required by the foreign keys.
+    sqlx::query("SELECT * FROM get_recent_messages('en', $1, $2, 1)")
+        .bind(USER_ID)
+        .bind(CHAT_ID)
+        .fetch_all(&pool)
+        .await
+        .expect("get_recent_messages failed");
+
+    sqlx::query("DELETE FROM messages WHERE user_telegram_id = $1 AND chat_telegram_id = $2")
+        .bind(USER_ID)
+        .bind(CHAT_ID)
+        .execute(&pool)
+        .await
+        .expect("cleanup failed");
+
+    for i in 1..=5 {
+        sqlx::query(
+            "INSERT INTO messages (user_telegram_id, chat_telegram_id, content, ia_response)
+             VALUES ($1, $2, $3, 'answer')",
+        )
+        .bind(USER_ID)
+        .bind(CHAT_ID)
+        .bind(format!("msg {i}"))
+        .execute(&pool)
+        .await
+        .expect("insert failed");
+    }
+
+    let pruned = prune_history(&pool, USER_ID, CHAT_ID, 2)
+        .await
+        .expect("prune failed");
+    assert_eq!(pruned, 3);
+    assert_eq!(active_rows(&pool).await, vec!["msg 4", "msg 5"]);
+
+    // Nothing left to prune on a second pass.
+    let pruned = prune_history(&pool, USER_ID, CHAT_ID, 2)
+        .await
+        .expect("prune failed");
+    assert_eq!(pruned, 0);
+
+    sqlx::query("DELETE FROM messages WHERE user_telegram_id = $1 AND chat_telegram_id = $2")
+        .bind(USER_ID)
+        .bind(CHAT_ID)
+        .execute(&pool)
+        .await
+        .expect("cleanup failed");
+}